@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueEnum};
+use directories::BaseDirs;
+use std::path::PathBuf;
+
+use crate::taskfile;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Simple terminal UI for Task / taskfile.dev")]
+pub struct Cli {
+    /// Initial search query
+    pub query: Option<String>,
+
+    /// Run the task right away if the query matches exactly one task
+    #[arg(short, long, requires = "query")]
+    pub run: bool,
+
+    /// Path to the Taskfile or to a directory containing it
+    #[arg(short, long, conflicts_with = "global")]
+    pub taskfile: Option<PathBuf>,
+
+    /// Directory to search for the Taskfile, instead of the current directory
+    #[arg(short, long, conflicts_with = "global")]
+    pub dir: Option<PathBuf>,
+
+    /// Use the global Taskfile in $HOME
+    #[arg(short, long)]
+    pub global: bool,
+
+    /// Validate the Taskfile and print all problems instead of starting the UI
+    #[arg(long, conflicts_with = "run")]
+    pub check: bool,
+
+    /// Print the graph of all tasks and includes instead of starting the UI
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["run", "check"])]
+    pub graph: Option<GraphFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl Cli {
+    /// Resolves the Taskfile to load, following the same rules as `task`.
+    pub fn taskfile_path(&self) -> Result<PathBuf> {
+        let dir = if self.global {
+            BaseDirs::new()
+                .map(|dirs| dirs.home_dir().to_path_buf())
+                .ok_or(anyhow!("home directory not found"))?
+        } else {
+            match &self.dir {
+                Some(dir) => dir.clone(),
+                None => std::env::current_dir()?,
+            }
+        };
+
+        let path = match &self.taskfile {
+            Some(taskfile) if dir.join(taskfile).is_dir() => {
+                taskfile::config::find_supported_file_in(&dir.join(taskfile))
+                    .ok_or(anyhow!("no supported file found in {}", taskfile.display()))?
+            }
+            Some(taskfile) => dir.join(taskfile),
+            // the global Taskfile is only looked up in $HOME itself
+            None if self.global => taskfile::config::find_supported_file_in(&dir)
+                .ok_or(anyhow!("no global Taskfile found in {}", dir.display()))?,
+            None => taskfile::config::find_supported_file(&dir)?,
+        };
+
+        Ok(path)
+    }
+}
@@ -1,12 +1,17 @@
+use crate::taskfile::{command::Invocation, config::Taskfile, error::ParseError};
 use crate::taskui::{App, Config};
 use anyhow::Result;
+use clap::Parser;
+use cli::{Cli, GraphFormat};
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::path::Path;
 use taskui::{
     event::{Event, EventHandler},
     terminal::UserInterface,
     update,
 };
 
+mod cli;
 mod taskfile;
 mod taskui;
 mod trace;
@@ -16,10 +21,46 @@ fn main() -> Result<()> {
 
     trace_dbg!("Starting taskui");
 
-    let taskfile = taskfile::config::load()?;
+    let cli = Cli::parse();
+    let taskfile_path = cli
+        .taskfile_path()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let loaded = taskfile::config::load(&taskfile_path);
+    if cli.check {
+        std::process::exit(check(&taskfile_path, &loaded));
+    }
+
+    if let Some(format) = cli.graph {
+        let taskfile = loaded.unwrap_or_else(|err| exit_with_error(err));
+        match format {
+            GraphFormat::Dot => print!("{}", taskfile::export::to_dot(&taskfile)),
+            GraphFormat::Mermaid => print!("{}", taskfile::export::to_mermaid(&taskfile)),
+        }
+        return Ok(());
+    }
+
+    let (taskfile, load_error) = match loaded {
+        Ok(taskfile) => (taskfile, None),
+        Err(err) => (Taskfile::default(), Some(err)),
+    };
 
     let cfg = Config::load();
     let mut app = App::new(cfg, taskfile);
+    app.load_error = load_error;
+
+    if let Some(query) = cli.query {
+        app.search(query);
+
+        if cli.run {
+            if let Some(task) = app.tasks.unique_match(&app.search) {
+                // the TUI still starts if the task asks for arguments or confirmation
+                app.run(Invocation::new(task.name));
+                if let Some(invocation) = app.task_to_exec {
+                    run(&taskfile_path, &invocation);
+                }
+            }
+        }
+    }
 
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
@@ -32,18 +73,55 @@ fn main() -> Result<()> {
         tui.draw(&mut app)?;
 
         match tui.events.next()? {
-            Event::Tick => {}
+            Event::Tick => app.tick(),
             Event::Key(key_event) => update(&mut app, key_event),
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
         };
     }
 
     tui.exit()?;
 
-    if let Some(task) = app.task_to_exec {
-        return taskfile::command::run_task(task.name);
+    if let Some(err) = app.load_error {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    if let Some(invocation) = app.task_to_exec {
+        run(&taskfile_path, &invocation);
     }
 
     Ok(())
 }
+
+/// Runs the task and exits with its exit code.
+fn run(taskfile_path: &Path, invocation: &Invocation) -> ! {
+    match taskfile::command::run_task(taskfile_path, invocation) {
+        Ok(code) => std::process::exit(code),
+        Err(err) => exit_with_error(err),
+    }
+}
+
+/// Prints the error the way `check` does and exits with code 1.
+fn exit_with_error(err: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", err);
+    std::process::exit(1);
+}
+
+/// Prints the problems found while loading the Taskfile, returning the exit code.
+fn check(taskfile_path: &Path, loaded: &Result<Taskfile, ParseError>) -> i32 {
+    match loaded {
+        Ok(taskfile) if taskfile.warnings.is_empty() => {
+            println!("{}: no problems found", taskfile_path.display());
+            0
+        }
+        Ok(taskfile) => {
+            for warning in &taskfile.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            1
+        }
+    }
+}
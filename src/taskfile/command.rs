@@ -1,19 +1,76 @@
-use anyhow::{Ok, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use colored::Colorize;
-use std::io::{BufRead, BufReader, Read};
-use std::process::{Command, Stdio};
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
 use std::thread;
 
-pub fn run_task(name: String) -> Result<()> {
-    let proc = Command::new("task")
-        .arg(name)
+/// A task to run together with the arguments given to it.
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    pub task: String,
+    /// Variables passed as `KEY=value` arguments.
+    pub vars: Vec<(String, String)>,
+    /// Arguments passed after `--`, which `task` provides as `CLI_ARGS`.
+    pub cli_args: Vec<String>,
+    /// Passes `--yes`, after the `prompt` of the task was confirmed.
+    pub yes: bool,
+}
+
+impl Invocation {
+    pub fn new(task: String) -> Invocation {
+        Invocation {
+            task,
+            vars: Vec::new(),
+            cli_args: Vec::new(),
+            yes: false,
+        }
+    }
+}
+
+/// Runs the task, printing its output, and returns the exit code of `task`.
+pub fn run_task(taskfile: &Path, invocation: &Invocation) -> Result<i32> {
+    check_version()?;
+
+    // registered before spawning, so no signal is missed while `task` starts
+    #[cfg(unix)]
+    let signals = register_signals()?;
+
+    let mut proc = Command::new("task")
+        .arg("--taskfile")
+        .arg(taskfile)
+        .args(invocation.yes.then_some("--yes"))
+        .arg(&invocation.task)
+        .args(
+            invocation
+                .vars
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        )
+        .args(cli_args(&invocation.cli_args))
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .unwrap();
+        .map_err(|e| spawn_error(e, "task"))?;
+
+    #[cfg(unix)]
+    let signals = match forward_signals(signals, proc.id()) {
+        Ok(handle) => handle,
+        Err(e) => {
+            _ = proc.kill();
+            _ = proc.wait();
+            return Err(e);
+        }
+    };
 
-    let stdout = proc.stdout.unwrap();
-    let stderr = proc.stderr.unwrap();
+    let stdout = proc
+        .stdout
+        .take()
+        .context("stdout of task is not captured")?;
+    let stderr = proc
+        .stderr
+        .take()
+        .context("stderr of task is not captured")?;
 
     let thread_print_out = thread::spawn(move || print_output(stdout));
     let thread_print_err = thread::spawn(move || print_output(stderr));
@@ -21,14 +78,154 @@ pub fn run_task(name: String) -> Result<()> {
     let _ = thread_print_out.join();
     let _ = thread_print_err.join();
 
-    Ok(())
+    let status = proc.wait()?;
+
+    #[cfg(unix)]
+    signals.close();
+
+    Ok(exit_code(status))
+}
+
+/// Makes sure `task` is installed and is v3, the version the Taskfile format
+/// is read for.
+fn check_version() -> Result<()> {
+    let output = Command::new("task")
+        .arg("--version")
+        .output()
+        .map_err(|e| spawn_error(e, "task --version"))?;
+    let version = String::from_utf8_lossy(&output.stdout);
+
+    // `Task version: v3.38.0 (h1:...)` or just `3.40.0` in newer versions
+    let major = version
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())
+        .and_then(|part| part.parse::<u32>().ok());
+    match major {
+        Some(3) => Ok(()),
+        Some(_) => bail!("taskui requires task v3, but found: {}", version.trim()),
+        // the check is only there to give a better error, so do not block on it
+        None => Ok(()),
+    }
+}
+
+fn spawn_error(err: std::io::Error, command: &str) -> anyhow::Error {
+    if err.kind() == ErrorKind::NotFound {
+        anyhow!(
+            "`task` was not found in PATH, see https://taskfile.dev/installation/ for how to install it"
+        )
+    } else {
+        anyhow!("failed to run `{}`: {}", command, err)
+    }
+}
+
+#[cfg(unix)]
+type Signals = signal_hook::iterator::SignalsInfo<signal_hook::iterator::exfiltrator::WithOrigin>;
+
+/// Keeps taskui running until `task` stops, so it can exit with its code.
+/// A handler is installed instead of ignoring the signals, which `task` would
+/// inherit.
+#[cfg(unix)]
+fn register_signals() -> Result<Signals> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+
+    Signals::new([SIGINT, SIGTERM]).context("failed to register signal handlers")
+}
+
+/// Passes the signals taskui receives on to `task`. `task` shares the process
+/// group of taskui, so an interrupt from the terminal already reaches it:
+/// forwarding it too would count twice towards the interrupts after which
+/// `task` force quits without running deferred commands. A SIGINT sent by
+/// another process, like SIGTERM, is only sent to taskui.
+#[cfg(unix)]
+fn forward_signals(mut signals: Signals, pid: u32) -> Result<signal_hook::iterator::Handle> {
+    use signal_hook::consts::SIGTERM;
+
+    let handle = signals.handle();
+
+    let forward = thread::Builder::new().spawn(move || {
+        for origin in signals.forever() {
+            // the terminal is not a process, the kernel sends its signals
+            if origin.signal != SIGTERM && origin.process.is_none() {
+                continue;
+            }
+            // SAFETY: kill has no memory safety requirements, at worst the
+            // process already exited and the signal is not delivered
+            unsafe {
+                libc::kill(pid as libc::pid_t, origin.signal);
+            }
+        }
+    });
+    if let Err(e) = forward {
+        handle.close();
+        return Err(anyhow!("failed to forward signals to `task`: {}", e));
+    }
+
+    Ok(handle)
+}
+
+/// The exit code of `task`, or like a shell 128 plus the signal that ended it.
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+
+    status.code().unwrap_or(1)
+}
+
+fn cli_args(args: &[String]) -> Vec<&str> {
+    if args.is_empty() {
+        return Vec::new();
+    }
+    std::iter::once("--")
+        .chain(args.iter().map(String::as_str))
+        .collect()
+}
+
+/// Splits a command line into arguments like a shell does, honouring single
+/// and double quotes and backslash escapes.
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => {
+                args.extend(arg.take());
+            }
+            (None, '\\') | (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    arg.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (Some(q), c) if q == c => quote = None,
+            (_, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+
+    args
 }
 
 fn print_output<T: Read>(stream: T) {
     let reader = BufReader::new(stream);
 
-    for line in reader.lines() {
-        let l = line.unwrap();
+    // output that is not valid UTF-8 is still printed, as far as possible
+    for line in reader.split(b'\n') {
+        let Ok(line) = line else {
+            break;
+        };
+        let l = String::from_utf8_lossy(&line);
+        let l = l.trim_end_matches('\r');
 
         if l.ends_with("is up to date") {
             println!("{}", l.purple());
@@ -41,3 +238,31 @@ fn print_output<T: Read>(stream: T) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(split_args("  a b\tc  "), ["a", "b", "c"]);
+        assert!(split_args("").is_empty());
+        assert!(split_args("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_whitespace() {
+        assert_eq!(split_args(r#"a "b c" 'd e'"#), ["a", "b c", "d e"]);
+        assert_eq!(split_args(r#"--msg="hello world""#), ["--msg=hello world"]);
+        assert_eq!(split_args(r#""" ''"#), ["", ""]);
+    }
+
+    #[test]
+    fn handles_escapes() {
+        assert_eq!(split_args(r"a\ b c\\d"), ["a b", r"c\d"]);
+        assert_eq!(split_args(r#""say \"hi\"""#), [r#"say "hi""#]);
+        // like a shell, backslashes are literal in single quotes
+        assert_eq!(split_args(r"'a\b'"), [r"a\b"]);
+        assert_eq!(split_args(r#"'"' "'""#), ["\"", "'"]);
+    }
+}
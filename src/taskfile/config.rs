@@ -1,16 +1,17 @@
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, Result};
 use serde_yaml::Value;
 use std::{
-    fs::{metadata, File},
-    path::PathBuf,
+    collections::HashSet,
+    fs::metadata,
+    path::{Component, Path, PathBuf},
 };
 
-#[derive(Clone, Debug)]
-pub struct Task {
-    pub name: String,
-    pub body: String,
-    pub internal: bool,
-}
+use super::error::{locate, InvalidValue, ParseError, Warning};
+use super::task::{
+    extract_bool, extract_string, extract_string_list, extract_vars, value_to_string, Task,
+    TaskfileEnv, Var,
+};
+use super::validate::{check_version, unknown_keys};
 
 #[derive(Clone, Debug)]
 struct Include {
@@ -18,26 +19,130 @@ struct Include {
     path: String,
     optional: bool,
     internal: bool,
+    dir: Option<String>,
+    vars: Vec<Var>,
+    aliases: Vec<String>,
+    flatten: bool,
+    excludes: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Taskfile {
+    pub path: PathBuf,
+    pub vars: Vec<Var>,
+    pub env: Vec<Var>,
+    pub dotenv: Vec<String>,
+    pub method: Option<String>,
+    pub tasks: Vec<Task>,
+    /// Problems found in the Taskfile or its includes that did not stop loading.
+    pub warnings: Vec<Warning>,
+}
+
+/// A Taskfile as read from disk, kept around to point errors to their location.
+struct Source {
+    path: PathBuf,
+    text: String,
+    yml: Value,
+    /// The Taskfiles that included this one, starting at the root Taskfile.
+    include_chain: Vec<PathBuf>,
+}
+
+impl Source {
+    fn read(path: &Path, include_chain: Vec<PathBuf>) -> Result<Source, ParseError> {
+        let error = |message: String| ParseError {
+            include_chain: include_chain.clone(),
+            ..ParseError::new(path, message)
+        };
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| error(format!("failed to read Taskfile: {}", e)))?;
+        let path = path
+            .canonicalize()
+            .map_err(|e| error(format!("failed to read Taskfile: {}", e)))?;
+        let yml = serde_yaml::from_str(&text).map_err(|e| ParseError {
+            position: e.location().map(|l| (l.line(), l.column())),
+            hint: Some("check the indentation and quoting around this position".to_string()),
+            ..error(format!("invalid YAML: {}", yaml_message(&e)))
+        })?;
+
+        Ok(Source {
+            path,
+            text,
+            yml,
+            include_chain,
+        })
+    }
+
+    fn error(&self, err: InvalidValue) -> ParseError {
+        ParseError {
+            file: self.path.clone(),
+            position: locate(&self.text, &err.path),
+            message: err.message,
+            include_chain: self.include_chain.clone(),
+            hint: err.hint,
+        }
+    }
+
+    /// Validates the version and keys of the Taskfile, rejecting unsupported
+    /// versions and collecting everything else as warnings.
+    fn validate(&self, warnings: &mut Vec<Warning>) -> Result<(), ParseError> {
+        if let Some(warning) = check_version(&self.yml).map_err(|e| self.error(e))? {
+            warnings.push(self.error(warning));
+        }
+        warnings.extend(unknown_keys(&self.yml).into_iter().map(|e| self.error(e)));
+
+        Ok(())
+    }
+
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
 }
 
-pub fn load() -> Result<Vec<Task>> {
-    let taskfile = File::open(find_supported_file()?).unwrap();
-    let taskfile_yml: Value = serde_yaml::from_reader(taskfile)?;
+/// serde_yaml appends the position to its messages, which is shown separately.
+fn yaml_message(err: &serde_yaml::Error) -> String {
+    let message = err.to_string();
+    match message.find(" at line ") {
+        Some(idx) => message[..idx].to_string(),
+        None => message,
+    }
+}
 
-    let mut tasks = get_tasks(&taskfile_yml)?;
-    let includes = get_includes(&taskfile_yml)?;
-    let current_path = std::env::current_dir().unwrap();
-    let included_tasks = handle_includes(
-        includes.unwrap_or(Vec::new()),
-        current_path.to_str().unwrap(),
-    )?;
+pub fn load(taskfile_path: &Path) -> Result<Taskfile, ParseError> {
+    let source = Source::read(taskfile_path, Vec::new())?;
+    let mut warnings = Vec::new();
+    source.validate(&mut warnings)?;
+
+    let mut tasks = get_tasks(&source, &mut warnings)?;
+    // includes are resolved relative to the directory the Taskfile was found in
+    let included_tasks = match get_includes(&source)? {
+        Some(includes) => handle_includes(includes, &source, &tasks, &mut warnings)?,
+        None => Vec::new(),
+    };
 
     tasks.extend(included_tasks);
 
-    Ok(tasks)
+    let yml = &source.yml;
+    Ok(Taskfile {
+        vars: extract_vars(yml, "vars").map_err(|e| source.error(e))?,
+        env: extract_vars(yml, "env").map_err(|e| source.error(e))?,
+        dotenv: extract_string_list(yml, "dotenv").map_err(|e| source.error(e))?,
+        method: extract_string(yml, "method").map_err(|e| source.error(e))?,
+        path: source.path,
+        tasks,
+        warnings,
+    })
+}
+
+/// Searches `dir` and its parent directories for a Taskfile, like `task` does.
+pub fn find_supported_file(dir: &Path) -> Result<PathBuf> {
+    match dir.ancestors().find_map(find_supported_file_in) {
+        Some(path) => Ok(path),
+        None => Err(anyhow!("no supported file found")),
+    }
 }
 
-fn find_supported_file() -> Result<&'static str> {
+pub fn find_supported_file_in(dir: &Path) -> Option<PathBuf> {
     // https://taskfile.dev/usage/#supported-file-names
     let file_names = [
         "Taskfile.yml",
@@ -50,49 +155,82 @@ fn find_supported_file() -> Result<&'static str> {
         "taskfile.dist.yaml",
     ];
 
-    let found = file_names
+    file_names
         .iter()
-        .find(|&file_name| metadata(file_name).is_ok())
-        .copied();
-
-    match found {
-        Some(file_name) => Ok(file_name),
-        None => Err(anyhow!("no supported file found")),
-    }
+        .map(|file_name| dir.join(file_name))
+        .find(|path| metadata(path).is_ok())
 }
 
-fn handle_includes(includes: Vec<Include>, current_path: &str) -> Result<Vec<Task>> {
+/// Loads the tasks of all includes of `source`. `local_tasks` are the tasks of
+/// the including Taskfile, which flattened includes must not collide with.
+fn handle_includes(
+    includes: Vec<Include>,
+    source: &Source,
+    local_tasks: &[Task],
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<Task>, ParseError> {
     let mut tasks: Vec<Task> = Vec::new();
 
-    for include in includes {
-        let include_path = PathBuf::from(current_path).join(include.path.clone());
-
-        match File::open(&include_path) {
-            Ok(taskfile) => {
-                let taskfile_yml: Value = serde_yaml::from_reader(taskfile)?;
-                let include_tasks = get_tasks(&taskfile_yml)?;
-                let include_tasks: Vec<Task> =
-                    prefix_tasks(include_tasks.clone(), include.name.clone());
-                let include_tasks: Vec<Task> = flag_internal_tasks(&include, include_tasks);
-                tasks.extend(include_tasks);
-
-                if let Some(sub_includes) = get_includes(&taskfile_yml)? {
-                    // remove filename from path
-                    let include_path = include_path.parent().unwrap().to_str().unwrap();
-                    let sub_include_tasks = handle_includes(sub_includes, include_path)?;
-                    let sub_include_tasks: Vec<Task> =
-                        prefix_tasks(sub_include_tasks.clone(), include.name.clone());
-                    let sub_include_tasks = flag_internal_tasks(&include, sub_include_tasks);
-                    tasks.extend(sub_include_tasks);
-                }
-            }
+    for mut include in includes {
+        include.dir = include.dir.map(|dir| include_dir(source, &dir));
+        let include_error = |message: String| {
+            InvalidValue::new("", message)
+                .at(&include.name)
+                .at("includes")
+        };
+        let include_path = match source.dir().join(&include.path).canonicalize() {
+            Ok(include_path) => include_path,
+            Err(_) if include.optional => continue,
             Err(_) => {
-                if include.optional {
-                    continue;
-                }
-                bail!("include not found: {}", include.path);
+                return Err(source.error(
+                    include_error(format!("include not found: {}", include.path))
+                        .with_hint("set `optional: true` to ignore a missing include"),
+                ))
             }
+        };
+
+        let mut include_chain = source.include_chain.clone();
+        include_chain.push(source.path.clone());
+        if include_chain.contains(&include_path) {
+            let cycle = format_cycle(&include_chain, &include_path);
+            return Err(source.error(include_error(format!("include cycle detected: {}", cycle))));
+        }
+
+        let included = Source::read(&include_path, include_chain)?;
+        included.validate(warnings)?;
+        let mut include_tasks = get_tasks(&included, warnings)?;
+        let taskfile_vars = extract_vars(&included.yml, "vars").map_err(|e| included.error(e))?;
+        let taskfile_env = TaskfileEnv {
+            taskfile: included.path.clone(),
+            env: extract_vars(&included.yml, "env").map_err(|e| included.error(e))?,
+        };
+
+        if let Some(sub_includes) = get_includes(&included)? {
+            let sub_include_tasks =
+                handle_includes(sub_includes, &included, &include_tasks, warnings)?;
+            include_tasks.extend(sub_include_tasks);
         }
+
+        include_tasks.retain(|task| !include.excludes.contains(&task.name));
+
+        let include_tasks = if include.flatten {
+            include_tasks
+        } else {
+            prefix_tasks(include_tasks, &include)
+        };
+        let include_tasks =
+            apply_include_options(&include, &taskfile_vars, &taskfile_env, include_tasks);
+
+        if include.flatten {
+            check_collisions(
+                local_tasks.iter().chain(tasks.iter()),
+                &include_tasks,
+                &include,
+            )
+            .map_err(|e| source.error(e.at(&include.name).at("includes")))?;
+        }
+
+        tasks.extend(include_tasks);
     }
 
     tasks.sort_by(|a, b| a.name.cmp(&b.name));
@@ -100,44 +238,169 @@ fn handle_includes(includes: Vec<Include>, current_path: &str) -> Result<Vec<Tas
     Ok(tasks)
 }
 
-fn flag_internal_tasks(include: &Include, tasks: Vec<Task>) -> Vec<Task> {
+/// Resolves the `dir` of an include against the Taskfile declaring it, like
+/// `task` does. The result is absolute, so the `dir` of outer includes applied
+/// later does not change it.
+fn include_dir(source: &Source, dir: &str) -> String {
+    // templates are resolved later, relative to the root Taskfile
+    if dir.contains("{{") {
+        return dir.to_string();
+    }
+
+    let dir: PathBuf = source
+        .dir()
+        .join(dir)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    dir.to_string_lossy().to_string()
+}
+
+/// Formats an include cycle with paths relative to the root Taskfile.
+fn format_cycle(include_chain: &[PathBuf], include_path: &Path) -> String {
+    let root_dir = include_chain[0].parent().unwrap_or(Path::new(""));
+    let chain: Vec<String> = include_chain
+        .iter()
+        .map(PathBuf::as_path)
+        .chain(std::iter::once(include_path))
+        .map(|path| {
+            path.strip_prefix(root_dir)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        })
+        .collect();
+
+    chain.join(" -> ")
+}
+
+fn apply_include_options(
+    include: &Include,
+    taskfile_vars: &[Var],
+    taskfile_env: &TaskfileEnv,
+    tasks: Vec<Task>,
+) -> Vec<Task> {
     tasks
         .into_iter()
         .map(|task| Task {
-            name: task.name,
-            body: task.body,
             internal: task.internal || include.internal,
+            dir: match (&include.dir, task.dir) {
+                (Some(include_dir), Some(task_dir)) => Some(
+                    PathBuf::from(include_dir)
+                        .join(task_dir)
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                (include_dir, task_dir) => task_dir.or(include_dir.clone()),
+            },
+            include_vars: include
+                .vars
+                .iter()
+                .chain(taskfile_vars.iter())
+                .chain(task.include_vars.iter())
+                .cloned()
+                .collect(),
+            include_env: task
+                .include_env
+                .iter()
+                .chain(Some(taskfile_env).filter(|taskfile_env| !taskfile_env.env.is_empty()))
+                .cloned()
+                .collect(),
+            ..task
         })
         .collect()
 }
 
-fn prefix_tasks(tasks: Vec<Task>, include_name: String) -> Vec<Task> {
+/// Prefixes task names and aliases with the include name and every include alias.
+fn prefix_tasks(tasks: Vec<Task>, include: &Include) -> Vec<Task> {
+    let namespaces: Vec<&String> = std::iter::once(&include.name)
+        .chain(include.aliases.iter())
+        .collect();
+
     tasks
         .into_iter()
-        .map(|task| Task {
-            name: format!("{}:{}", include_name, task.name),
-            body: task.body,
-            internal: task.internal,
+        .map(|task| {
+            let aliases = namespaces
+                .iter()
+                .flat_map(|namespace| {
+                    task.names()
+                        .map(move |name| format!("{}:{}", namespace, name))
+                })
+                .filter(|alias| *alias != format!("{}:{}", include.name, task.name))
+                .collect();
+
+            let namespace = match task.namespace.as_str() {
+                "" => include.name.clone(),
+                namespace => format!("{}:{}", include.name, namespace),
+            };
+
+            Task {
+                name: format!("{}:{}", include.name, task.name),
+                aliases,
+                namespace,
+                ..task
+            }
         })
         .collect()
 }
 
-fn get_tasks(taskfile_yml: &Value) -> Result<Vec<Task>> {
+fn check_collisions<'a>(
+    existing: impl Iterator<Item = &'a Task>,
+    flattened: &[Task],
+    include: &Include,
+) -> Result<(), InvalidValue> {
+    let existing: HashSet<&str> = existing.flat_map(Task::names).collect();
+
+    for task in flattened {
+        if let Some(name) = task.names().find(|name| existing.contains(name)) {
+            return Err(InvalidValue::new(
+                "",
+                format!(
+                    "found multiple tasks ({}) included by flattened include {}",
+                    name, include.name
+                ),
+            )
+            .with_hint("exclude the task with `excludes` or remove `flatten`"));
+        }
+    }
+
+    Ok(())
+}
+
+fn get_tasks(source: &Source, warnings: &mut Vec<Warning>) -> Result<Vec<Task>, ParseError> {
     let mut tasks: Vec<Task> = Vec::new();
 
-    if let Some(task_mapping) = taskfile_yml.get("tasks").and_then(Value::as_mapping) {
+    if let Some(task_mapping) = source.yml.get("tasks").and_then(Value::as_mapping) {
         for (key, body) in task_mapping {
-            let task_name = key.as_str().unwrap().to_string();
-            let internal = extract_bool(body, "internal", false)?;
+            let task_name = match key.as_str() {
+                Some(task_name) => task_name,
+                None => {
+                    return Err(source.error(
+                        InvalidValue::new(&value_to_string(key), "task names must be strings")
+                            .at("tasks")
+                            .with_hint("quote the task name"),
+                    ))
+                }
+            };
 
+            let mut invalid = Vec::new();
+            let task = Task::parse(task_name, body, &mut invalid)
+                .map_err(|e| source.error(e.at(task_name).at("tasks")))?;
+            warnings.extend(
+                invalid
+                    .into_iter()
+                    .map(|e| source.error(e.at(task_name).at("tasks"))),
+            );
             tasks.push(Task {
-                name: task_name,
-                body: serde_yaml::to_string(body).unwrap_or_else(|_| "no content".to_string()),
-                internal,
+                taskfile: source.path.clone(),
+                ..task
             });
         }
     } else {
-        bail!("failed to extract tasks")
+        return Err(source.error(
+            InvalidValue::new("", "failed to extract tasks")
+                .with_hint("a Taskfile needs a `tasks` mapping"),
+        ));
     }
 
     tasks.sort_by(|a, b| a.name.cmp(&b.name));
@@ -145,22 +408,16 @@ fn get_tasks(taskfile_yml: &Value) -> Result<Vec<Task>> {
     Ok(tasks)
 }
 
-fn get_includes(taskfile_yml: &Value) -> Result<Option<Vec<Include>>> {
+fn get_includes(source: &Source) -> Result<Option<Vec<Include>>, ParseError> {
     let mut includes: Vec<Include> = Vec::new();
 
-    if let Some(include_mapping) = taskfile_yml.get("includes").and_then(Value::as_mapping) {
+    if let Some(include_mapping) = source.yml.get("includes").and_then(Value::as_mapping) {
         for (key, value) in include_mapping {
-            let name = extract_include_name(key);
-            let path = extract_include_path(value)?;
-            let optional = extract_bool(value, "optional", false)?;
-            let internal = extract_bool(value, "internal", false)?;
-
-            includes.push(Include {
-                name,
-                path,
-                optional,
-                internal,
-            });
+            let name = extract_include_name(key).map_err(|e| source.error(e.at("includes")))?;
+            let include = parse_include(name.clone(), value)
+                .map_err(|e| source.error(e.at(&name).at("includes")))?;
+
+            includes.push(include);
         }
     } else {
         return Ok(None);
@@ -169,50 +426,187 @@ fn get_includes(taskfile_yml: &Value) -> Result<Option<Vec<Include>>> {
     Ok(Some(includes))
 }
 
-fn extract_include_name(include_key: &Value) -> String {
-    include_key.as_str().unwrap().to_string()
+fn parse_include(name: String, value: &Value) -> Result<Include, InvalidValue> {
+    Ok(Include {
+        name,
+        path: extract_include_path(value)?,
+        optional: extract_bool(value, "optional", false)?,
+        internal: extract_bool(value, "internal", false)?,
+        dir: extract_string(value, "dir")?,
+        vars: extract_vars(value, "vars")?,
+        aliases: extract_string_list(value, "aliases")?,
+        flatten: extract_bool(value, "flatten", false)?,
+        excludes: extract_string_list(value, "excludes")?,
+    })
+}
+
+fn extract_include_name(include_key: &Value) -> Result<String, InvalidValue> {
+    match include_key.as_str() {
+        Some(name) => Ok(name.to_string()),
+        None => Err(InvalidValue::new(
+            &value_to_string(include_key),
+            "include names must be strings",
+        )
+        .with_hint("quote the include name")),
+    }
 }
 
-fn extract_include_path(include_yml: &Value) -> Result<String> {
+fn extract_include_path(include_yml: &Value) -> Result<String, InvalidValue> {
     let path = match include_yml {
         Value::String(path) if path.ends_with(".yml") || path.ends_with(".yaml") => {
             path.to_string()
         }
         Value::String(path) if path.ends_with('/') => format!("{}Taskfile.yml", path),
         Value::String(path) => format!("{}/Taskfile.yml", path),
-        Value::Mapping(v) => {
-            if let Some(taskfile) = v.get(&Value::String("taskfile".to_string())) {
-                if let Value::String(s) = taskfile {
-                    if s.ends_with(".yml") || s.ends_with(".yaml") {
-                        s.to_string()
-                    } else {
-                        bail!("value of taskfile key must end with .yml or .yaml")
-                    }
-                } else {
-                    bail!("value of taskfile key must be of type string")
-                }
-            } else {
-                bail!("taskfile key not found in include mapping")
+        Value::Mapping(v) => match v.get("taskfile") {
+            Some(Value::String(s)) if s.ends_with(".yml") || s.ends_with(".yaml") => s.to_string(),
+            Some(Value::String(_)) => {
+                return Err(InvalidValue::new(
+                    "taskfile",
+                    "value of taskfile key must end with .yml or .yaml",
+                ))
             }
-        }
-        _ => bail!("invalid include found"),
+            Some(_) => {
+                return Err(InvalidValue::new(
+                    "taskfile",
+                    "value of taskfile key must be of type string",
+                ))
+            }
+            None => {
+                return Err(
+                    InvalidValue::new("", "taskfile key not found in include mapping")
+                        .with_hint("set `taskfile` to the path of the included Taskfile"),
+                )
+            }
+        },
+        _ => return Err(InvalidValue::new("", "invalid include found")),
     };
 
     Ok(path)
 }
 
-fn extract_bool(yml: &Value, field: &str, default: bool) -> Result<bool> {
-    match yml {
-        Value::Mapping(v) => {
-            if let Some(value) = v
-                .get(&Value::String(field.to_string()))
-                .and_then(Value::as_bool)
-            {
-                Ok(value)
-            } else {
-                Ok(default)
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the Taskfiles to a new directory and loads the root Taskfile.
+    fn load_files(name: &str, files: &[(&str, &str)]) -> (PathBuf, Result<Taskfile, ParseError>) {
+        let dir = std::env::temp_dir().join(format!("taskui-{}-{}", name, std::process::id()));
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
         }
-        _ => Ok(false),
+
+        let loaded = load(&dir.join("Taskfile.yml"));
+        let dir = dir.canonicalize().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        (dir, loaded)
+    }
+
+    fn names(taskfile: &Taskfile) -> Vec<&str> {
+        taskfile
+            .tasks
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn flattens_and_excludes_tasks() {
+        let (_, loaded) = load_files(
+            "flatten",
+            &[
+                (
+                    "Taskfile.yml",
+                    "version: '3'\nincludes:\n  lib:\n    taskfile: lib.yml\n    flatten: true\n    excludes: [skip]\n  docs: docs.yml\ntasks:\n  hello: echo hello\n",
+                ),
+                ("lib.yml", "version: '3'\ntasks:\n  build: echo\n  skip: echo\n"),
+                ("docs.yml", "version: '3'\ntasks:\n  serve: echo\n"),
+            ],
+        );
+
+        let taskfile = loaded.unwrap();
+        assert_eq!(names(&taskfile), ["hello", "build", "docs:serve"]);
+        assert_eq!(taskfile.tasks[2].namespace, "docs");
+    }
+
+    #[test]
+    fn rejects_flattened_collisions() {
+        let (_, loaded) = load_files(
+            "collision",
+            &[
+                (
+                    "Taskfile.yml",
+                    "version: '3'\nincludes:\n  lib:\n    taskfile: lib.yml\n    flatten: true\ntasks:\n  build: echo\n",
+                ),
+                ("lib.yml", "version: '3'\ntasks:\n  build: echo\n"),
+            ],
+        );
+
+        let err = loaded.unwrap_err();
+        assert_eq!(
+            err.message,
+            "found multiple tasks (build) included by flattened include lib"
+        );
+        assert_eq!(err.position, Some((3, 3)));
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let (_, loaded) = load_files(
+            "cycle",
+            &[
+                (
+                    "Taskfile.yml",
+                    "version: '3'\nincludes:\n  b: b/Taskfile.yml\ntasks:\n  a: echo\n",
+                ),
+                (
+                    "b/Taskfile.yml",
+                    "version: '3'\nincludes:\n  root: ../Taskfile.yml\ntasks:\n  b: echo\n",
+                ),
+            ],
+        );
+
+        let err = loaded.unwrap_err();
+        assert_eq!(
+            err.message,
+            "include cycle detected: Taskfile.yml -> b/Taskfile.yml -> Taskfile.yml"
+        );
+    }
+
+    #[test]
+    fn resolves_include_dir_relative_to_the_including_taskfile() {
+        let (dir, loaded) = load_files(
+            "include-dir",
+            &[
+                (
+                    "Taskfile.yml",
+                    "version: '3'\nincludes:\n  sub:\n    taskfile: sub/Taskfile.yml\n    dir: sub\ntasks:\n  a: echo\n",
+                ),
+                (
+                    "sub/Taskfile.yml",
+                    "version: '3'\nincludes:\n  nested:\n    taskfile: nested/Taskfile.yml\n    dir: ./nested\ntasks:\n  b: echo\n",
+                ),
+                (
+                    "sub/nested/Taskfile.yml",
+                    "version: '3'\ntasks:\n  c: echo\n  d:\n    dir: out\n    cmds: [echo]\n",
+                ),
+            ],
+        );
+
+        let taskfile = loaded.unwrap();
+        let task_dir = |name: &str| {
+            let task = taskfile
+                .tasks
+                .iter()
+                .find(|task| task.name == name)
+                .unwrap();
+            task.dir.as_ref().map(PathBuf::from)
+        };
+        assert_eq!(task_dir("a"), None);
+        assert_eq!(task_dir("sub:b"), Some(dir.join("sub")));
+        assert_eq!(task_dir("sub:nested:c"), Some(dir.join("sub/nested")));
+        assert_eq!(task_dir("sub:nested:d"), Some(dir.join("sub/nested/out")));
     }
 }
@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::config::Taskfile;
+use super::shell::ShellVars;
+use super::task::{Task, Var, VarValue};
+use super::template::{self, Scope};
+use super::variables::task_scope;
+
+/// The environment a task runs with, on top of the environment of `task` itself.
+#[derive(Debug, Default)]
+pub struct Environment {
+    pub entries: Vec<EnvEntry>,
+    /// dotenv files that are referenced but do not exist.
+    pub missing: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+    pub source: String,
+}
+
+/// Collects the environment of a task. Like `task`, earlier sources win: the
+/// task's `env`, the task's dotenv files, the `env` of the included Taskfiles,
+/// the Taskfile's `env` and finally the Taskfile's dotenv files, where the
+/// first file that sets a key wins.
+pub fn task_environment(
+    taskfile: &Taskfile,
+    task: &Task,
+    shell: Option<&mut ShellVars>,
+) -> Environment {
+    let mut shell = shell;
+    let scope = task_scope(taskfile, task, shell.as_deref_mut());
+    let root_dir = taskfile.path.parent().unwrap_or(Path::new(""));
+    let taskfile_dir = task.taskfile.parent().unwrap_or(Path::new(""));
+
+    let mut env = Environment::default();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    let mut add = |env: &mut Environment, key: String, value: String, source: String| {
+        if seen.insert(key.clone()) {
+            env.entries.push(EnvEntry { key, value, source });
+        }
+    };
+
+    for (key, value) in resolve_env(&task.env, &scope, shell.as_deref_mut()) {
+        add(&mut env, key, value, "task env".to_string());
+    }
+    for (path, result) in read_dotenv_files(&task.dotenv, taskfile_dir, &scope) {
+        match result {
+            Some(entries) => {
+                for (key, value) in entries {
+                    add(&mut env, key, value, display_path(&path, root_dir));
+                }
+            }
+            None => env.missing.push(path),
+        }
+    }
+    for taskfile_env in &task.include_env {
+        let source = format!("{} env", display_path(&taskfile_env.taskfile, root_dir));
+        for (key, value) in resolve_env(&taskfile_env.env, &scope, shell.as_deref_mut()) {
+            add(&mut env, key, value, source.clone());
+        }
+    }
+    for (key, value) in resolve_env(&taskfile.env, &scope, shell) {
+        add(&mut env, key, value, "Taskfile env".to_string());
+    }
+    for (path, result) in read_dotenv_files(&taskfile.dotenv, root_dir, &scope) {
+        match result {
+            Some(entries) => {
+                for (key, value) in entries {
+                    add(&mut env, key, value, display_path(&path, root_dir));
+                }
+            }
+            None => env.missing.push(path),
+        }
+    }
+
+    env.entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+    env
+}
+
+fn resolve_env(
+    env: &[Var],
+    scope: &Scope,
+    mut shell: Option<&mut ShellVars>,
+) -> Vec<(String, String)> {
+    let dir = PathBuf::from(
+        scope
+            .get("TASK_DIR")
+            .map(template::format_value)
+            .unwrap_or_default(),
+    );
+
+    env.iter()
+        .map(|var| {
+            let value = match &var.value {
+                VarValue::Static(value) => template::render(&template::format_value(value), scope),
+                VarValue::Ref(reference) => template::evaluate(reference, scope)
+                    .map(|value| template::format_value(&value))
+                    .unwrap_or_else(|| var.value.to_string()),
+                VarValue::Sh(sh) | VarValue::Evaluated { sh, .. } => match shell.as_deref_mut() {
+                    Some(shell) => VarValue::Evaluated {
+                        sh: sh.clone(),
+                        output: shell.evaluate(&template::render(sh, scope), &dir),
+                    }
+                    .to_string(),
+                    None => var.value.to_string(),
+                },
+            };
+
+            (var.name.clone(), value)
+        })
+        .collect()
+}
+
+type DotenvFile = (PathBuf, Option<Vec<(String, String)>>);
+
+/// Reads all dotenv files, `None` marks a file that does not exist.
+fn read_dotenv_files(files: &[String], dir: &Path, scope: &Scope) -> Vec<DotenvFile> {
+    files
+        .iter()
+        .map(|file| {
+            let path = dir.join(template::render(file, scope));
+            let entries = std::fs::read_to_string(&path).ok().map(|s| parse(&s));
+            (path, entries)
+        })
+        .collect()
+}
+
+fn display_path(path: &Path, root_dir: &Path) -> String {
+    path.strip_prefix(root_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Parses the content of a dotenv file.
+pub fn parse(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+
+            Some((key.trim().to_string(), parse_value(value.trim())))
+        })
+        .collect()
+}
+
+fn parse_value(value: &str) -> String {
+    if let Some(quoted) = value.strip_prefix('"') {
+        let quoted = &quoted[..quoted.rfind('"').unwrap_or(quoted.len())];
+        return quoted
+            .replace("\\n", "\n")
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+    }
+    if let Some(quoted) = value.strip_prefix('\'') {
+        return quoted[..quoted.rfind('\'').unwrap_or(quoted.len())].to_string();
+    }
+
+    // unquoted values end at an inline comment
+    match value.find(" #") {
+        Some(idx) => value[..idx].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Whether the key of an environment variable looks like it holds a secret.
+pub fn is_secret(key: &str) -> bool {
+    let key = key.to_uppercase();
+    let markers = [
+        "SECRET",
+        "TOKEN",
+        "PASSWORD",
+        "PASSWD",
+        "CREDENTIAL",
+        "PRIVATE",
+        "AUTH",
+        "API_KEY",
+        "APIKEY",
+        "ACCESS_KEY",
+    ];
+
+    markers.iter().any(|marker| key.contains(marker)) || key.ends_with("_KEY") || key == "KEY"
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml::Value;
+
+    use super::*;
+    use crate::taskfile::task::TaskfileEnv;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<Var> {
+        vars.iter()
+            .map(|(name, value)| Var {
+                name: name.to_string(),
+                value: VarValue::Static(Value::String(value.to_string())),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_dotenv_files() {
+        let content = r#"
+# comment
+A=1
+export B = two
+C="quoted # not a comment\nline"
+D='single $X'
+E=value # comment
+F=
+not a variable
+"#;
+        let expected = [
+            ("A", "1"),
+            ("B", "two"),
+            ("C", "quoted # not a comment\nline"),
+            ("D", "single $X"),
+            ("E", "value"),
+            ("F", ""),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(parse(content), expected);
+    }
+
+    #[test]
+    fn earlier_sources_win() {
+        let dir = std::env::temp_dir().join(format!("taskui-dotenv-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/task.env"), "K=task dotenv\nK2=task dotenv\n").unwrap();
+        std::fs::write(dir.join("root.env"), "K=root dotenv\nK5=root dotenv\n").unwrap();
+
+        let task = Task {
+            name: "a".to_string(),
+            env: vars(&[("K", "task env")]),
+            dotenv: vec!["task.env".to_string(), "missing.env".to_string()],
+            include_env: vec![TaskfileEnv {
+                taskfile: dir.join("sub/Taskfile.yml"),
+                env: vars(&[
+                    ("K", "include env"),
+                    ("K2", "include env"),
+                    ("K3", "include env"),
+                ]),
+            }],
+            taskfile: dir.join("sub/Taskfile.yml"),
+            ..Default::default()
+        };
+        let taskfile = Taskfile {
+            path: dir.join("Taskfile.yml"),
+            env: vars(&[("K3", "Taskfile env"), ("K4", "Taskfile env")]),
+            dotenv: vec!["root.env".to_string()],
+            tasks: vec![task.clone()],
+            ..Default::default()
+        };
+
+        let env = task_environment(&taskfile, &task, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let entries: Vec<(&str, &str, &str)> = env
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key.as_str(),
+                    entry.value.as_str(),
+                    entry.source.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("K", "task env", "task env"),
+                ("K2", "task dotenv", "sub/task.env"),
+                ("K3", "include env", "sub/Taskfile.yml env"),
+                ("K4", "Taskfile env", "Taskfile env"),
+                ("K5", "root dotenv", "root.env"),
+            ]
+        );
+        assert_eq!(env.missing, [dir.join("sub/missing.env")]);
+    }
+}
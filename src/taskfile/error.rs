@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+
+/// A problem with a value of a YAML document. `path` holds the keys and
+/// sequence indices leading to the value, relative to the node it was found in.
+#[derive(Debug)]
+pub struct InvalidValue {
+    pub path: Vec<String>,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl InvalidValue {
+    pub fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: if key.is_empty() {
+                Vec::new()
+            } else {
+                vec![key.to_string()]
+            },
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// Moves the value below `key`.
+    pub fn at(mut self, key: impl ToString) -> Self {
+        self.path.insert(0, key.to_string());
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl std::fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+/// An error while loading a Taskfile, pointing to where it happened.
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    pub file: PathBuf,
+    /// Line and column, both starting at 1.
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+    /// The Taskfiles that included `file`, starting at the root Taskfile.
+    pub include_chain: Vec<PathBuf>,
+    pub hint: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(file: &Path, message: impl Into<String>) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            position: None,
+            message: message.into(),
+            include_chain: Vec::new(),
+            hint: None,
+        }
+    }
+
+    /// The location of the error as `file:line:column`.
+    pub fn location(&self) -> String {
+        let file = display_path(&self.file);
+        match self.position {
+            Some((line, column)) => format!("{}:{}:{}", file, line, column),
+            None => file,
+        }
+    }
+
+    pub fn included_from(&self) -> Option<String> {
+        if self.include_chain.is_empty() {
+            return None;
+        }
+
+        let chain: Vec<String> = self
+            .include_chain
+            .iter()
+            .map(|path| display_path(path))
+            .collect();
+
+        Some(chain.join(" -> "))
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location(), self.message)?;
+        if let Some(chain) = self.included_from() {
+            write!(f, "\n  included from: {}", chain)?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A problem that does not prevent a Taskfile from being loaded.
+pub type Warning = ParseError;
+
+fn display_path(path: &Path) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    path.strip_prefix(&cwd)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+    item: bool,
+}
+
+/// Finds the line and column of the node at `path` in a YAML document. Only
+/// block style is followed, for anything else the position of the closest
+/// parent found is returned.
+pub fn locate(source: &str, path: &[String]) -> Option<(usize, usize)> {
+    let lines = block_lines(source);
+    let mut range = 0..lines.len();
+    let mut position = None;
+
+    for segment in path {
+        let indent = match lines[range.clone()].iter().map(|line| line.indent).min() {
+            Some(indent) => indent,
+            None => break,
+        };
+        let mut children = range.clone().filter(|&i| lines[i].indent == indent);
+
+        let is_sequence = lines[range.start].item;
+        let found = match segment.parse::<usize>() {
+            Ok(index) if is_sequence => children.filter(|&i| lines[i].item).nth(index),
+            _ => children.find(|&i| !lines[i].item && is_key(lines[i].text, segment)),
+        };
+        let line = match found {
+            Some(line) => line,
+            None => break,
+        };
+
+        position = Some((lines[line].number, lines[line].indent + 1));
+
+        // a key may be followed by sequence items on the same indentation
+        let end = (line + 1..range.end)
+            .find(|&i| {
+                lines[i].indent < indent
+                    || (lines[i].indent == indent && (lines[line].item || !lines[i].item))
+            })
+            .unwrap_or(range.end);
+        range = line + 1..end;
+    }
+
+    position
+}
+
+/// Splits a YAML document into its non-empty lines. The content of a sequence
+/// item is added as a separate line, as it starts a new block.
+fn block_lines(source: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+
+    for (idx, raw) in source.lines().enumerate() {
+        let text = raw.trim_start();
+        if text.is_empty() || text.starts_with('#') || text.starts_with("---") {
+            continue;
+        }
+
+        let indent = raw.len() - text.len();
+        let item = text == "-" || text.starts_with("- ");
+        lines.push(Line {
+            number: idx + 1,
+            indent,
+            text,
+            item,
+        });
+
+        if item && text.len() > 2 {
+            let rest = text[2..].trim_start();
+            lines.push(Line {
+                number: idx + 1,
+                indent: indent + text.len() - rest.len(),
+                text: rest,
+                item: rest == "-" || rest.starts_with("- "),
+            });
+        }
+    }
+
+    lines
+}
+
+fn is_key(text: &str, key: &str) -> bool {
+    [
+        key.to_string(),
+        format!("\"{}\"", key),
+        format!("'{}'", key),
+    ]
+    .iter()
+    .any(|quoted| {
+        text.strip_prefix(quoted.as_str())
+            .map(|rest| rest.trim_start().starts_with(':'))
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TASKFILE: &str = r#"version: '3'
+
+# build tasks
+tasks:
+  build:
+    desc: Build
+    cmds:
+      - echo one
+      - task: test
+        vars: { A: 1 }
+  "quoted":
+    deps:
+    - a
+    -   b
+"#;
+
+    fn at(path: &[&str]) -> Option<(usize, usize)> {
+        let path: Vec<String> = path.iter().map(|segment| segment.to_string()).collect();
+        locate(TASKFILE, &path)
+    }
+
+    #[test]
+    fn locates_keys() {
+        assert_eq!(at(&["version"]), Some((1, 1)));
+        assert_eq!(at(&["tasks"]), Some((4, 1)));
+        assert_eq!(at(&["tasks", "build"]), Some((5, 3)));
+        assert_eq!(at(&["tasks", "build", "desc"]), Some((6, 5)));
+        assert_eq!(at(&["tasks", "quoted"]), Some((11, 3)));
+    }
+
+    #[test]
+    fn locates_sequence_items() {
+        assert_eq!(at(&["tasks", "build", "cmds", "0"]), Some((8, 7)));
+        assert_eq!(at(&["tasks", "build", "cmds", "1"]), Some((9, 7)));
+        assert_eq!(at(&["tasks", "build", "cmds", "1", "vars"]), Some((10, 9)));
+        assert_eq!(at(&["tasks", "quoted", "deps", "1"]), Some((14, 5)));
+    }
+
+    #[test]
+    fn falls_back_to_the_closest_parent() {
+        assert_eq!(at(&["tasks", "build", "missing"]), Some((5, 3)));
+        assert_eq!(at(&["tasks", "build", "cmds", "5"]), Some((7, 5)));
+        assert_eq!(at(&["missing"]), None);
+        assert_eq!(locate("", &["tasks".to_string()]), None);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeSet;
+
+use super::config::Taskfile;
+use super::graph::{calls, lookup, Call};
+use super::task::Task;
+
+struct Edge {
+    from: String,
+    to: String,
+    call: Call,
+    /// The target does not exist, `to` is the reference as written.
+    missing: bool,
+}
+
+/// The references between all tasks, each one only once.
+fn edges(taskfile: &Taskfile) -> Vec<Edge> {
+    let mut seen = BTreeSet::new();
+    let mut edges = Vec::new();
+
+    for task in &taskfile.tasks {
+        for (call, reference) in calls(taskfile, task) {
+            let (to, missing) = match lookup(&taskfile.tasks, &task.namespace, &reference) {
+                Some(target) => (target.name.clone(), false),
+                None => (reference, true),
+            };
+
+            if seen.insert((task.name.clone(), to.clone(), call == Call::Cmd)) {
+                edges.push(Edge {
+                    from: task.name.clone(),
+                    to,
+                    call,
+                    missing,
+                });
+            }
+        }
+    }
+
+    edges
+}
+
+/// All namespaces with tasks and their parents, sorted so parents come first.
+fn namespaces(taskfile: &Taskfile) -> Vec<String> {
+    let mut namespaces = BTreeSet::new();
+
+    for task in &taskfile.tasks {
+        let mut namespace = task.namespace.as_str();
+        while !namespace.is_empty() {
+            namespaces.insert(namespace.to_string());
+            namespace = parent(namespace);
+        }
+    }
+
+    namespaces.into_iter().collect()
+}
+
+fn parent(namespace: &str) -> &str {
+    namespace.rsplit_once(':').map(|(n, _)| n).unwrap_or("")
+}
+
+fn short_name(namespace: &str) -> &str {
+    namespace
+        .rsplit_once(':')
+        .map(|(_, n)| n)
+        .unwrap_or(namespace)
+}
+
+/// Renders the task graph as Graphviz DOT. Includes become nested clusters,
+/// `task:` calls are dashed and internal tasks are greyed out.
+pub fn to_dot(taskfile: &Taskfile) -> String {
+    let mut out = String::from("digraph tasks {\n  rankdir=LR;\n  node [shape=box];\n");
+    let namespaces = namespaces(taskfile);
+    let edges = edges(taskfile);
+
+    dot_namespace(&mut out, taskfile, &namespaces, "", 1);
+
+    let missing: BTreeSet<&str> = edges
+        .iter()
+        .filter(|edge| edge.missing)
+        .map(|edge| edge.to.as_str())
+        .collect();
+    for name in &missing {
+        out.push_str(&format!(
+            "  {} [label={}, color=red, fontcolor=red, style=dashed];\n",
+            dot_id(&format!("missing:{}", name)),
+            dot_id(name)
+        ));
+    }
+
+    for edge in &edges {
+        let to = if edge.missing {
+            format!("missing:{}", edge.to)
+        } else {
+            edge.to.clone()
+        };
+        let style = match edge.call {
+            Call::Dep => "",
+            Call::Cmd => " [style=dashed]",
+        };
+        out.push_str(&format!(
+            "  {} -> {}{};\n",
+            dot_id(&edge.from),
+            dot_id(&to),
+            style
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn dot_namespace(
+    out: &mut String,
+    taskfile: &Taskfile,
+    namespaces: &[String],
+    namespace: &str,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+
+    for task in tasks_in(taskfile, namespace) {
+        let style = if task.internal {
+            ", style=\"dashed,filled\", fillcolor=lightgrey, fontcolor=dimgrey"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "{}{} [label={}{}];\n",
+            indent,
+            dot_id(&task.name),
+            dot_id(&task.name),
+            style
+        ));
+    }
+
+    for (idx, child) in namespaces
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| parent(child) == namespace)
+    {
+        out.push_str(&format!("{}subgraph cluster_{} {{\n", indent, idx));
+        out.push_str(&format!(
+            "{}  label={};\n",
+            indent,
+            dot_id(short_name(child))
+        ));
+        dot_namespace(out, taskfile, namespaces, child, depth + 1);
+        out.push_str(&format!("{}}}\n", indent));
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Renders the task graph as a Mermaid flowchart. Includes become nested
+/// subgraphs, `task:` calls are dotted and internal tasks are greyed out.
+pub fn to_mermaid(taskfile: &Taskfile) -> String {
+    let mut out = String::from("flowchart LR\n");
+    let namespaces = namespaces(taskfile);
+    let edges = edges(taskfile);
+
+    mermaid_namespace(&mut out, taskfile, &namespaces, "", 1);
+
+    let missing: BTreeSet<&str> = edges
+        .iter()
+        .filter(|edge| edge.missing)
+        .map(|edge| edge.to.as_str())
+        .collect();
+    let missing_id = |name: &str| {
+        let idx = missing.iter().position(|m| *m == name).unwrap_or_default();
+        format!("m{}", idx)
+    };
+    for name in &missing {
+        out.push_str(&format!(
+            "  {}[\"{}\"]:::missing\n",
+            missing_id(name),
+            mermaid_label(name)
+        ));
+    }
+
+    for edge in &edges {
+        let to = if edge.missing {
+            missing_id(&edge.to)
+        } else {
+            mermaid_id(taskfile, &edge.to)
+        };
+        let arrow = match edge.call {
+            Call::Dep => "-->",
+            Call::Cmd => "-.->",
+        };
+        out.push_str(&format!(
+            "  {} {} {}\n",
+            mermaid_id(taskfile, &edge.from),
+            arrow,
+            to
+        ));
+    }
+
+    out.push_str("  classDef internal fill:#eee,stroke:#999,color:#666,stroke-dasharray: 4 4\n");
+    out.push_str("  classDef missing stroke:#f00,color:#f00,stroke-dasharray: 4 4\n");
+    out
+}
+
+fn mermaid_namespace(
+    out: &mut String,
+    taskfile: &Taskfile,
+    namespaces: &[String],
+    namespace: &str,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+
+    for task in tasks_in(taskfile, namespace) {
+        let class = if task.internal { ":::internal" } else { "" };
+        out.push_str(&format!(
+            "{}{}[\"{}\"]{}\n",
+            indent,
+            mermaid_id(taskfile, &task.name),
+            mermaid_label(&task.name),
+            class
+        ));
+    }
+
+    for (idx, child) in namespaces
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| parent(child) == namespace)
+    {
+        out.push_str(&format!(
+            "{}subgraph ns{}[\"{}\"]\n",
+            indent,
+            idx,
+            mermaid_label(short_name(child))
+        ));
+        mermaid_namespace(out, taskfile, namespaces, child, depth + 1);
+        out.push_str(&format!("{}end\n", indent));
+    }
+}
+
+/// Task names may contain characters Mermaid does not allow in ids, so tasks
+/// are numbered instead.
+fn mermaid_id(taskfile: &Taskfile, name: &str) -> String {
+    let idx = taskfile
+        .tasks
+        .iter()
+        .position(|task| task.name == name)
+        .unwrap_or_default();
+    format!("t{}", idx)
+}
+
+fn mermaid_label(name: &str) -> String {
+    name.replace('"', "#quot;")
+}
+
+fn tasks_in<'a>(taskfile: &'a Taskfile, namespace: &'a str) -> impl Iterator<Item = &'a Task> {
+    taskfile
+        .tasks
+        .iter()
+        .filter(move |task| task.namespace == namespace)
+}
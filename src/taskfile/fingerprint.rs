@@ -0,0 +1,338 @@
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use xxhash_rust::xxh3::Xxh3;
+
+use super::config::Taskfile;
+use super::task::Task;
+use super::variables::resolve_task;
+
+/// Whether the `sources` of a task changed since it last ran.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    UpToDate,
+    Stale,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct State {
+    pub status: Status,
+    /// When the task last ran successfully, as recorded in the state `task`
+    /// keeps in `.task`.
+    pub last_run: Option<SystemTime>,
+}
+
+/// Tells whether a task with `sources` is up to date, like `task --status`
+/// does. `None` means it cannot be told without running anything.
+pub fn state(taskfile: &Taskfile, task: &Task) -> Option<State> {
+    if task.sources.is_empty() {
+        return None;
+    }
+
+    let task = resolve_task(taskfile, task, None);
+    // globs depending on dynamic variables cannot be expanded
+    if task
+        .sources
+        .iter()
+        .chain(task.generates.iter())
+        .any(|pattern| pattern.contains("{{"))
+    {
+        return None;
+    }
+
+    let method = method(taskfile, &task);
+    if method == "none" {
+        return None;
+    }
+
+    let state_file = temp_dir(taskfile)
+        .join(method)
+        .join(normalize_filename(state_name(&task)));
+    let last_run = modified(&state_file);
+    let stale = State {
+        status: Status::Stale,
+        last_run,
+    };
+
+    let dir = task_dir(taskfile, &task);
+    let sources = globs(&dir, &task.sources);
+    if sources.is_empty() {
+        return Some(stale);
+    }
+    // like `task`, every generates glob has to match something
+    if task
+        .generates
+        .iter()
+        .filter(|pattern| !pattern.starts_with('!'))
+        .any(|pattern| expand(&dir, pattern).is_empty())
+    {
+        return Some(stale);
+    }
+
+    let up_to_date = if method == "timestamp" {
+        let mut generates = globs(&dir, &task.generates);
+        if last_run.is_none() {
+            return Some(stale);
+        }
+        generates.push(state_file);
+
+        let newest_generate = generates.iter().filter_map(|path| modified(path)).max()?;
+        !sources
+            .iter()
+            .filter_map(|path| modified(path))
+            .any(|time| time > newest_generate)
+    } else {
+        match std::fs::read_to_string(&state_file) {
+            Ok(stored) => stored.trim() == checksum(&sources),
+            Err(_) => false,
+        }
+    };
+
+    Some(State {
+        status: if up_to_date {
+            Status::UpToDate
+        } else {
+            Status::Stale
+        },
+        last_run,
+    })
+}
+
+/// The fingerprint `task` stores for the checksum method: a 128 bit XXH3 hash
+/// over the name and content of every source file.
+fn checksum(sources: &[PathBuf]) -> String {
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0; 128 * 1024];
+
+    for source in sources {
+        let name = source.file_name().unwrap_or_default();
+        hasher.update(name.to_string_lossy().as_bytes());
+
+        if let Ok(mut file) = File::open(source) {
+            while let Ok(n @ 1..) = file.read(&mut buf) {
+                hasher.update(&buf[..n]);
+            }
+        }
+    }
+
+    let hash = hasher.digest128();
+    format!("{:x}{:x}", (hash >> 64) as u64, hash as u64)
+}
+
+/// The name `task` files the state of a task under.
+fn state_name(task: &Task) -> &str {
+    match &task.label {
+        Some(label) if !label.is_empty() => label,
+        _ => &task.name,
+    }
+}
+
+fn method<'a>(taskfile: &'a Taskfile, task: &'a Task) -> &'a str {
+    task.method
+        .as_deref()
+        .or(taskfile.method.as_deref())
+        .unwrap_or("checksum")
+}
+
+/// The directory a resolved task runs in.
+pub fn task_dir(taskfile: &Taskfile, task: &Task) -> PathBuf {
+    let root_dir = taskfile.path.parent().unwrap_or(Path::new(""));
+    match &task.dir {
+        Some(dir) => root_dir.join(dir),
+        None => root_dir.to_path_buf(),
+    }
+}
+
+/// The directory `task` keeps its state in.
+fn temp_dir(taskfile: &Taskfile) -> PathBuf {
+    let root_dir = taskfile.path.parent().unwrap_or(Path::new(""));
+    match std::env::var("TASK_TEMP_DIR") {
+        Ok(dir) if !dir.is_empty() => root_dir.join(dir),
+        _ => root_dir.join(".task"),
+    }
+}
+
+/// Replaces characters `task` does not use in state file names.
+fn normalize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'A'..='z' | '0'..='9' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+/// Expands globs like `task` does: patterns starting with `!` remove files
+/// matched before and directories are skipped.
+pub fn globs(dir: &Path, patterns: &[String]) -> Vec<PathBuf> {
+    let mut files = BTreeSet::new();
+
+    for pattern in patterns {
+        match pattern.strip_prefix('!') {
+            Some(exclude) => {
+                for file in expand(dir, exclude) {
+                    files.remove(&file);
+                }
+            }
+            None => files.extend(expand(dir, pattern)),
+        }
+    }
+
+    // sorted as strings, like `task` does before hashing them
+    let mut files: Vec<PathBuf> = files.into_iter().collect();
+    files.sort_by_key(|file| file.to_string_lossy().to_string());
+    files
+}
+
+/// The files matched by a single glob, relative patterns are relative to `dir`.
+pub fn expand(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    match glob::glob(&absolute_pattern(dir, pattern)) {
+        Ok(paths) => paths
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn absolute_pattern(dir: &Path, pattern: &str) -> String {
+    if Path::new(pattern).is_absolute() {
+        pattern.to_string()
+    } else {
+        format!(
+            "{}/{}",
+            glob::Pattern::escape(&dir.to_string_lossy()),
+            pattern
+        )
+    }
+}
+
+/// Whether `path` is matched by the globs, without looking at the file system,
+/// so deleted files are matched as well. Like in `globs`, later patterns win.
+pub fn is_matched(dir: &Path, patterns: &[String], path: &Path) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    patterns.iter().fold(false, |matched, pattern| {
+        let (exclude, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.as_str()),
+        };
+        match glob::Pattern::new(&absolute_pattern(dir, pattern)) {
+            Ok(pattern) if pattern.matches_path_with(path, options) => !exclude,
+            _ => matched,
+        }
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The files matched by the globs of `sources` or `generates`.
+#[derive(Clone, Debug, Default)]
+pub struct Matches {
+    pub files: Vec<MatchedFile>,
+    /// Globs that match no file at all.
+    pub unmatched: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+pub fn matches(dir: &Path, patterns: &[String]) -> Matches {
+    let unmatched = patterns
+        .iter()
+        .filter(|pattern| !pattern.starts_with('!'))
+        .filter(|pattern| expand(dir, pattern).is_empty())
+        .cloned()
+        .collect();
+    let files = globs(dir, patterns)
+        .into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            MatchedFile {
+                size: metadata.as_ref().map(|m| m.len()).unwrap_or_default(),
+                modified: metadata.and_then(|m| m.modified().ok()),
+                path,
+            }
+        })
+        .collect();
+
+    Matches { files, unmatched }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("taskui-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn checksums_like_task() {
+        // XXH3 128 of no input, from the reference test vectors of xxHash
+        assert_eq!(checksum(&[]), "99aa06d3014798d86001c324468d497f");
+
+        let dir = scratch_dir("checksum");
+        let content = "x".repeat(200 * 1024);
+        std::fs::write(dir.join("a.txt"), &content).unwrap();
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        let sources = [dir.join("a.txt"), dir.join("b.txt")];
+        let sum = checksum(&sources);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // the file names are hashed too, halves are printed without padding
+        let hash = xxhash_rust::xxh3::xxh3_128(format!("a.txt{}b.txtb", content).as_bytes());
+        assert_eq!(sum, format!("{:x}{:x}", (hash >> 64) as u64, hash as u64));
+    }
+
+    #[test]
+    fn compares_with_the_stored_checksum() {
+        let dir = scratch_dir("state");
+        std::fs::write(dir.join("main.go"), "package main").unwrap();
+        let task = Task {
+            name: "ns:build".to_string(),
+            sources: vec!["*.go".to_string()],
+            ..Default::default()
+        };
+        let taskfile = Taskfile {
+            path: dir.join("Taskfile.yml"),
+            tasks: vec![task.clone()],
+            ..Default::default()
+        };
+
+        let status = |taskfile: &Taskfile| state(taskfile, &task).map(|state| state.status);
+        assert_eq!(status(&taskfile), Some(Status::Stale));
+
+        let state_dir = dir.join(".task/checksum");
+        std::fs::create_dir_all(&state_dir).unwrap();
+        let sum = checksum(&[dir.join("main.go")]);
+        std::fs::write(state_dir.join("ns-build"), format!("{}\n", sum)).unwrap();
+        assert_eq!(status(&taskfile), Some(Status::UpToDate));
+
+        std::fs::write(dir.join("main.go"), "package changed").unwrap();
+        let changed = status(&taskfile);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(changed, Some(Status::Stale));
+    }
+
+    #[test]
+    fn normalizes_state_file_names() {
+        assert_eq!(normalize_filename("build"), "build");
+        assert_eq!(normalize_filename("docs:serve"), "docs-serve");
+        assert_eq!(normalize_filename("my_task v2/linux"), "my_task-v2-linux");
+        assert_eq!(normalize_filename("größe"), "gr--e");
+    }
+}
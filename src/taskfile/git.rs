@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Lists the files changed in the git working tree of `dir`: staged and
+/// unstaged changes against `HEAD` as well as untracked files.
+pub fn changed_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+
+    // a repository without commits has nothing to diff against
+    let diff = match git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]) {
+        Ok(_) => git(dir, &["diff", "--name-only", "-z", "HEAD"])?,
+        Err(_) => String::new(),
+    };
+    // run from the root, as `ls-files` lists paths relative to the current directory
+    let untracked = git(&root, &["ls-files", "-z", "--others", "--exclude-standard"])?;
+
+    let mut files: Vec<PathBuf> = diff
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| root.join(path))
+        .chain(
+            untracked
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(|path| root.join(path)),
+        )
+        .collect();
+
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
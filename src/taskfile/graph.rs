@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use super::config::Taskfile;
+use super::task::{Cmd, Task};
+use super::variables::resolve_task;
+
+/// How a task triggers another task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Call {
+    Dep,
+    Cmd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeState {
+    Found,
+    /// No task with this name exists.
+    Missing,
+    /// The task is already part of the path leading to it.
+    Cycle,
+    /// The task is shown with its children earlier in the tree.
+    Repeated,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    /// The name of the task, or the reference as written if it does not exist.
+    pub name: String,
+    /// How the parent node triggers this task, `None` for the root.
+    pub call: Option<Call>,
+    pub state: NodeState,
+    pub children: Vec<Node>,
+}
+
+/// Builds the tree of tasks triggered by `task`, through its `deps` and the
+/// `task:` calls of its `cmds`. The children of a task are only added where it
+/// first appears, tasks reached through several paths would otherwise grow the
+/// tree exponentially.
+pub fn dependency_tree(taskfile: &Taskfile, task: &Task) -> Node {
+    let mut path = Vec::new();
+    let mut expanded = HashSet::new();
+
+    Node {
+        name: task.name.clone(),
+        call: None,
+        state: NodeState::Found,
+        children: child_nodes(taskfile, task, &mut path, &mut expanded),
+    }
+}
+
+fn child_nodes(
+    taskfile: &Taskfile,
+    task: &Task,
+    path: &mut Vec<String>,
+    expanded: &mut HashSet<String>,
+) -> Vec<Node> {
+    path.push(task.name.clone());
+    expanded.insert(task.name.clone());
+
+    let children = calls(taskfile, task)
+        .into_iter()
+        .map(
+            |(call, reference)| match lookup(&taskfile.tasks, &task.namespace, &reference) {
+                None => Node {
+                    name: reference,
+                    call: Some(call),
+                    state: NodeState::Missing,
+                    children: Vec::new(),
+                },
+                Some(target) if path.contains(&target.name) => Node {
+                    name: target.name.clone(),
+                    call: Some(call),
+                    state: NodeState::Cycle,
+                    children: Vec::new(),
+                },
+                Some(target) if expanded.contains(&target.name) => Node {
+                    name: target.name.clone(),
+                    call: Some(call),
+                    state: NodeState::Repeated,
+                    children: Vec::new(),
+                },
+                Some(target) => Node {
+                    name: target.name.clone(),
+                    call: Some(call),
+                    state: NodeState::Found,
+                    children: child_nodes(taskfile, target, path, expanded),
+                },
+            },
+        )
+        .collect();
+
+    path.pop();
+
+    children
+}
+
+/// The task and the tasks it triggers directly or indirectly, each once, in
+/// the order they are first reached.
+pub fn triggered<'a>(taskfile: &'a Taskfile, task: &'a Task) -> Vec<&'a Task> {
+    let mut tasks = Vec::new();
+    visit(taskfile, task, &mut tasks);
+
+    tasks
+}
+
+fn visit<'a>(taskfile: &'a Taskfile, task: &'a Task, tasks: &mut Vec<&'a Task>) {
+    if tasks.iter().any(|visited| visited.name == task.name) {
+        return;
+    }
+    tasks.push(task);
+
+    for (_, reference) in calls(taskfile, task) {
+        if let Some(target) = lookup(&taskfile.tasks, &task.namespace, &reference) {
+            visit(taskfile, target, tasks);
+        }
+    }
+}
+
+/// The references to other tasks of a task, with templates in their names
+/// resolved as far as possible.
+pub fn calls(taskfile: &Taskfile, task: &Task) -> Vec<(Call, String)> {
+    let task = resolve_task(taskfile, task, None);
+
+    let deps = task.deps.iter().map(|dep| (Call::Dep, dep.task.clone()));
+    let cmds = task.cmds.iter().filter_map(|cmd| match cmd {
+        Cmd::Task { task, .. } => Some((Call::Cmd, task.clone())),
+        Cmd::Defer(cmd) => match cmd.as_ref() {
+            Cmd::Task { task, .. } => Some((Call::Cmd, task.clone())),
+            _ => None,
+        },
+        Cmd::Shell { .. } => None,
+    });
+
+    deps.chain(cmds).collect()
+}
+
+/// Finds the task a reference of a task in `namespace` points to. Like `task`,
+/// references are relative to the namespace, a leading `:` refers to the root
+/// Taskfile. Wildcard tasks are only matched if no name matches exactly.
+pub fn lookup<'a>(tasks: &'a [Task], namespace: &str, reference: &str) -> Option<&'a Task> {
+    let name = match reference.strip_prefix(':') {
+        Some(name) => name.to_string(),
+        None if namespace.is_empty() => reference.to_string(),
+        None => format!("{}:{}", namespace, reference),
+    };
+
+    tasks
+        .iter()
+        .find(|task| task.names().any(|n| n == name))
+        .or_else(|| {
+            tasks
+                .iter()
+                .find(|task| task.wildcard_match(&name).is_some())
+        })
+}
+
+/// Finds all tasks that depend on or call `task`, sorted by name.
+pub fn used_by<'a>(taskfile: &'a Taskfile, task: &Task) -> Vec<(&'a Task, Call)> {
+    let mut callers: Vec<(&Task, Call)> = taskfile
+        .tasks
+        .iter()
+        .flat_map(|caller| {
+            calls(taskfile, caller)
+                .into_iter()
+                .filter(|(_, reference)| {
+                    lookup(&taskfile.tasks, &caller.namespace, reference)
+                        .map(|target| target.name == task.name)
+                        .unwrap_or(false)
+                })
+                .map(move |(call, _)| (caller, call))
+        })
+        .collect();
+
+    callers.sort_by_key(|(caller, call)| (caller.name.clone(), *call == Call::Cmd));
+    callers.dedup_by(|a, b| a.0.name == b.0.name && a.1 == b.1);
+
+    callers
+}
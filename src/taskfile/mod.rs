@@ -1,2 +1,13 @@
 pub mod command;
 pub mod config;
+pub mod dotenv;
+pub mod error;
+pub mod export;
+pub mod fingerprint;
+pub mod git;
+pub mod graph;
+pub mod shell;
+pub mod task;
+pub mod template;
+pub mod validate;
+pub mod variables;
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Runs the commands of dynamic `sh:` variables and caches their output, so each
+/// command is only run once per directory.
+pub struct ShellVars {
+    timeout: Duration,
+    cache: HashMap<(String, PathBuf), Result<String, String>>,
+}
+
+impl ShellVars {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn evaluate(&mut self, cmd: &str, dir: &Path) -> Result<String, String> {
+        let timeout = self.timeout;
+
+        self.cache
+            .entry((cmd.to_string(), dir.to_path_buf()))
+            .or_insert_with(|| run(cmd, dir, timeout))
+            .clone()
+    }
+}
+
+fn run(cmd: &str, dir: &Path, timeout: Duration) -> Result<String, String> {
+    let mut proc = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let mut stdout = proc.stdout.take().unwrap();
+    let mut stderr = proc.stderr.take().unwrap();
+    let read_out = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        out
+    });
+    let read_err = thread::spawn(move || {
+        let mut err = String::new();
+        let _ = stderr.read_to_string(&mut err);
+        err
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        match proc.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() >= timeout => {
+                let _ = proc.kill();
+                let _ = proc.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs_f32()));
+            }
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+
+    let out = read_out.join().unwrap_or_default();
+    let err = read_err.join().unwrap_or_default();
+
+    if status.success() {
+        // like `task`, only the trailing newline is removed
+        Ok(out.trim_end_matches(['\r', '\n']).to_string())
+    } else {
+        Err(match err.trim() {
+            "" => status.to_string(),
+            err => err.to_string(),
+        })
+    }
+}
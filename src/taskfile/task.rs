@@ -0,0 +1,745 @@
+use serde_yaml::Value;
+use std::path::PathBuf;
+
+use super::error::InvalidValue;
+use super::template::{go_arch, go_os};
+
+type Result<T, E = InvalidValue> = std::result::Result<T, E>;
+
+// https://taskfile.dev/reference/schema/#task
+#[derive(Clone, Debug, Default)]
+pub struct Task {
+    pub name: String,
+    pub desc: Option<String>,
+    pub summary: Option<String>,
+    pub cmds: Vec<Cmd>,
+    pub deps: Vec<Dep>,
+    pub vars: Vec<Var>,
+    /// Variables of the includes that led to this task and of the included
+    /// Taskfiles, outermost first.
+    pub include_vars: Vec<Var>,
+    pub env: Vec<Var>,
+    /// The `env` of the included Taskfiles that led to this task, innermost
+    /// first.
+    pub include_env: Vec<TaskfileEnv>,
+    pub dotenv: Vec<String>,
+    pub dir: Option<String>,
+    pub aliases: Vec<String>,
+    pub preconditions: Vec<Precondition>,
+    /// Names of the variables that have to be set to run the task.
+    pub requires: Vec<String>,
+    pub sources: Vec<String>,
+    pub generates: Vec<String>,
+    /// How `task` decides whether the task is up to date, `checksum` if unset.
+    pub method: Option<String>,
+    pub platforms: Vec<String>,
+    /// Questions to confirm before the task runs.
+    pub prompt: Vec<String>,
+    pub silent: bool,
+    pub interactive: bool,
+    pub internal: bool,
+    pub label: Option<String>,
+    pub run: Run,
+    /// The task as written in the Taskfile, as YAML.
+    pub definition: String,
+    /// The Taskfile this task is defined in.
+    pub taskfile: PathBuf,
+    /// The include namespace the task was loaded into, empty for the root
+    /// Taskfile and flattened includes of it.
+    pub namespace: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum Cmd {
+    Shell {
+        cmd: String,
+        silent: bool,
+        ignore_error: bool,
+        platforms: Vec<String>,
+    },
+    Task {
+        task: String,
+        vars: Vec<Var>,
+        silent: bool,
+    },
+    Defer(Box<Cmd>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Dep {
+    pub task: String,
+    pub vars: Vec<Var>,
+    pub silent: bool,
+}
+
+/// The top-level `env` of a Taskfile.
+#[derive(Clone, Debug)]
+pub struct TaskfileEnv {
+    pub taskfile: PathBuf,
+    pub env: Vec<Var>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Var {
+    pub name: String,
+    pub value: VarValue,
+}
+
+#[derive(Clone, Debug)]
+pub enum VarValue {
+    Static(Value),
+    Sh(String),
+    Ref(String),
+    /// The result of running the command of a `sh:` variable. This is never
+    /// parsed, it only shows up when variables are resolved.
+    Evaluated {
+        sh: String,
+        output: Result<String, String>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Precondition {
+    pub sh: String,
+    pub msg: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Run {
+    #[default]
+    Always,
+    Once,
+    WhenChanged,
+}
+
+impl std::fmt::Display for Run {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Run::Always => write!(f, "always"),
+            Run::Once => write!(f, "once"),
+            Run::WhenChanged => write!(f, "when_changed"),
+        }
+    }
+}
+
+impl std::fmt::Display for Precondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.msg {
+            Some(msg) => write!(f, "{} ({})", self.sh, msg),
+            None => write!(f, "{}", self.sh),
+        }
+    }
+}
+
+impl Task {
+    /// Parses a task. Invalid values of keys that are only displayed do not
+    /// fail the task, they are added to `warnings` and ignored instead.
+    pub fn parse(name: &str, body: &Value, warnings: &mut Vec<InvalidValue>) -> Result<Task> {
+        let task = match body {
+            // shorthand: `task: echo hello`
+            Value::String(cmd) => Task {
+                name: name.to_string(),
+                cmds: vec![shell_cmd(cmd)],
+                ..Default::default()
+            },
+            // shorthand: `task: [echo hello, echo world]`
+            Value::Sequence(_) => Task {
+                name: name.to_string(),
+                cmds: parse_cmds(body)?,
+                ..Default::default()
+            },
+            Value::Mapping(_) => {
+                let mut cmds = parse_cmds(body.get("cmds").unwrap_or(&Value::Null))
+                    .map_err(|e| e.at("cmds"))?;
+                if let Some(cmd) = body.get("cmd") {
+                    cmds.insert(0, parse_cmd(cmd).map_err(|e| e.at("cmd"))?);
+                }
+
+                Task {
+                    name: name.to_string(),
+                    desc: lenient(extract_string(body, "desc"), warnings),
+                    summary: lenient(extract_string(body, "summary"), warnings),
+                    cmds,
+                    deps: parse_deps(body.get("deps").unwrap_or(&Value::Null))
+                        .map_err(|e| e.at("deps"))?,
+                    vars: extract_vars(body, "vars")?,
+                    include_vars: Vec::new(),
+                    include_env: Vec::new(),
+                    env: extract_vars(body, "env")?,
+                    dotenv: extract_string_list(body, "dotenv")?,
+                    dir: extract_string(body, "dir")?,
+                    aliases: extract_string_list(body, "aliases")?,
+                    preconditions: parse_preconditions(
+                        body.get("preconditions").unwrap_or(&Value::Null),
+                    )
+                    .map_err(|e| e.at("preconditions"))?,
+                    requires: parse_requires(body.get("requires").unwrap_or(&Value::Null))
+                        .map_err(|e| e.at("requires"))?,
+                    sources: extract_string_list(body, "sources")?,
+                    generates: extract_string_list(body, "generates")?,
+                    method: extract_string(body, "method")?,
+                    platforms: extract_string_list(body, "platforms")?,
+                    prompt: parse_prompt(body.get("prompt").unwrap_or(&Value::Null))
+                        .map_err(|e| e.at("prompt"))?,
+                    silent: lenient(extract_bool(body, "silent", false), warnings),
+                    interactive: lenient(extract_bool(body, "interactive", false), warnings),
+                    internal: extract_bool(body, "internal", false)?,
+                    label: extract_string(body, "label")?,
+                    run: lenient(parse_run(body.get("run")), warnings),
+                    definition: String::new(),
+                    taskfile: PathBuf::new(),
+                    namespace: String::new(),
+                }
+            }
+            Value::Null => Task {
+                name: name.to_string(),
+                ..Default::default()
+            },
+            _ => {
+                return Err(
+                    InvalidValue::new("", format!("invalid definition of task {}", name))
+                        .with_hint("a task must be a command, a list of commands or a mapping"),
+                )
+            }
+        };
+
+        Ok(Task {
+            definition: serde_yaml::to_string(body).unwrap_or_default(),
+            ..task
+        })
+    }
+
+    /// Whether the task runs on the current OS and architecture. Entries of
+    /// `platforms` are an OS, an architecture or both, like `linux/amd64`.
+    pub fn matches_platform(&self) -> bool {
+        self.platforms.is_empty()
+            || self
+                .platforms
+                .iter()
+                .any(|platform| match platform.split_once('/') {
+                    Some((os, arch)) => os == go_os() && arch == go_arch(),
+                    None => platform == go_os() || platform == go_arch(),
+                })
+    }
+
+    /// Whether a template in the commands, variables or preconditions of the
+    /// task refers to the variable, like `{{.CLI_ARGS}}`.
+    pub fn references(&self, var: &str) -> bool {
+        let reference = format!(".{}", var);
+
+        self.cmds.iter().any(|cmd| cmd_references(cmd, &reference))
+            || self
+                .deps
+                .iter()
+                .any(|dep| vars_reference(&dep.vars, &reference))
+            || vars_reference(&self.vars, &reference)
+            || vars_reference(&self.env, &reference)
+            || self
+                .preconditions
+                .iter()
+                .any(|precondition| precondition.sh.contains(&reference))
+    }
+
+    /// Whether the name contains `*`, which `task` matches against the name
+    /// given when calling it.
+    pub fn is_wildcard(&self) -> bool {
+        self.name.contains('*')
+    }
+
+    /// The parts of `name` matched by the wildcards of the task name, which
+    /// `task` exposes as `MATCH`. Like `task`, earlier wildcards match greedily.
+    pub fn wildcard_match(&self, name: &str) -> Option<Vec<String>> {
+        if !self.is_wildcard() {
+            return None;
+        }
+        let parts: Vec<&str> = self.name.split('*').collect();
+        match_parts(&parts, name)
+    }
+
+    /// The task name followed by all of its aliases.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Matches `name` against the literal `parts` of a wildcard name, returning
+/// what the wildcards between them matched.
+fn match_parts(parts: &[&str], name: &str) -> Option<Vec<String>> {
+    let rest = name.strip_prefix(parts[0])?;
+    if parts.len() == 1 {
+        return rest.is_empty().then(Vec::new);
+    }
+
+    // try the longest match first
+    (0..=rest.len())
+        .rev()
+        .filter(|end| rest.is_char_boundary(*end))
+        .find_map(|end| {
+            let mut matches = match_parts(&parts[1..], &rest[end..])?;
+            matches.insert(0, rest[..end].to_string());
+            Some(matches)
+        })
+}
+
+fn cmd_references(cmd: &Cmd, reference: &str) -> bool {
+    match cmd {
+        Cmd::Shell { cmd, .. } => cmd.contains(reference),
+        Cmd::Task { task, vars, .. } => task.contains(reference) || vars_reference(vars, reference),
+        Cmd::Defer(cmd) => cmd_references(cmd, reference),
+    }
+}
+
+fn vars_reference(vars: &[Var], reference: &str) -> bool {
+    vars.iter().any(|var| match &var.value {
+        VarValue::Static(value) => value_to_string(value).contains(reference),
+        VarValue::Sh(text) | VarValue::Ref(text) => text.contains(reference),
+        VarValue::Evaluated { sh, .. } => sh.contains(reference),
+    })
+}
+
+fn shell_cmd(cmd: &str) -> Cmd {
+    Cmd::Shell {
+        cmd: cmd.to_string(),
+        silent: false,
+        ignore_error: false,
+        platforms: Vec::new(),
+    }
+}
+
+fn parse_cmds(yml: &Value) -> Result<Vec<Cmd>> {
+    match yml {
+        Value::Null => Ok(Vec::new()),
+        Value::Sequence(cmds) => cmds
+            .iter()
+            .enumerate()
+            .map(|(idx, cmd)| parse_cmd(cmd).map_err(|e| e.at(idx)))
+            .collect(),
+        _ => Err(InvalidValue::new("", "cmds must be a list")
+            .with_hint("list each command on its own line, starting with `- `")),
+    }
+}
+
+fn parse_cmd(yml: &Value) -> Result<Cmd> {
+    let cmd = match yml {
+        Value::String(cmd) => shell_cmd(cmd),
+        Value::Mapping(_) => {
+            if let Some(defer) = yml.get("defer") {
+                Cmd::Defer(Box::new(parse_cmd(defer).map_err(|e| e.at("defer"))?))
+            } else if let Some(task) = extract_string(yml, "task")? {
+                Cmd::Task {
+                    task,
+                    vars: extract_vars(yml, "vars")?,
+                    silent: extract_bool(yml, "silent", false)?,
+                }
+            } else if let Some(cmd) = extract_string(yml, "cmd")? {
+                Cmd::Shell {
+                    cmd,
+                    silent: extract_bool(yml, "silent", false)?,
+                    ignore_error: extract_bool(yml, "ignore_error", false)?,
+                    platforms: extract_string_list(yml, "platforms")?,
+                }
+            } else {
+                return Err(InvalidValue::new(
+                    "",
+                    "command must contain either a cmd, task or defer key",
+                ));
+            }
+        }
+        _ => return Err(InvalidValue::new("", "command must be a string or mapping")),
+    };
+
+    Ok(cmd)
+}
+
+fn parse_deps(yml: &Value) -> Result<Vec<Dep>> {
+    let deps = match yml {
+        Value::Null => return Ok(Vec::new()),
+        Value::Sequence(deps) => deps,
+        _ => return Err(InvalidValue::new("", "deps must be a list")),
+    };
+
+    deps.iter()
+        .enumerate()
+        .map(|(idx, dep)| match dep {
+            Value::String(task) => Ok(Dep {
+                task: task.to_string(),
+                vars: Vec::new(),
+                silent: false,
+            }),
+            Value::Mapping(_) => match extract_string(dep, "task").map_err(|e| e.at(idx))? {
+                Some(task) => Ok(Dep {
+                    task,
+                    vars: extract_vars(dep, "vars").map_err(|e| e.at(idx))?,
+                    silent: extract_bool(dep, "silent", false)?,
+                }),
+                None => Err(InvalidValue::new("", "task key not found in dependency").at(idx)),
+            },
+            _ => Err(InvalidValue::new("", "dependency must be a string or mapping").at(idx)),
+        })
+        .collect()
+}
+
+/// Parses the variables defined below `field`, like `vars` or `env`.
+pub fn extract_vars(yml: &Value, field: &str) -> Result<Vec<Var>> {
+    parse_vars(yml.get(field).unwrap_or(&Value::Null)).map_err(|e| e.at(field))
+}
+
+fn parse_vars(yml: &Value) -> Result<Vec<Var>> {
+    let vars = match yml {
+        Value::Null => return Ok(Vec::new()),
+        Value::Mapping(vars) => vars,
+        _ => {
+            return Err(InvalidValue::new("", "vars must be a mapping")
+                .with_hint("define variables as `NAME: value`"))
+        }
+    };
+
+    let mut parsed: Vec<Var> = Vec::new();
+
+    for (key, value) in vars {
+        let name = match key.as_str() {
+            Some(name) => name.to_string(),
+            None => {
+                return Err(InvalidValue::new(
+                    &value_to_string(key),
+                    "variable names must be strings",
+                ))
+            }
+        };
+
+        let value = if let Some(sh) = extract_string(value, "sh").map_err(|e| e.at(&name))? {
+            VarValue::Sh(sh)
+        } else if let Some(reference) = extract_string(value, "ref").map_err(|e| e.at(&name))? {
+            VarValue::Ref(reference)
+        } else if let Some(map) = value.get("map") {
+            VarValue::Static(map.clone())
+        } else {
+            VarValue::Static(value.clone())
+        };
+
+        parsed.push(Var { name, value });
+    }
+
+    Ok(parsed)
+}
+
+fn parse_preconditions(yml: &Value) -> Result<Vec<Precondition>> {
+    let preconditions = match yml {
+        Value::Null => return Ok(Vec::new()),
+        Value::Sequence(preconditions) => preconditions,
+        _ => return Err(InvalidValue::new("", "preconditions must be a list")),
+    };
+
+    preconditions
+        .iter()
+        .enumerate()
+        .map(|(idx, precondition)| match precondition {
+            Value::String(sh) => Ok(Precondition {
+                sh: sh.to_string(),
+                msg: None,
+            }),
+            Value::Mapping(_) => match extract_string(precondition, "sh").map_err(|e| e.at(idx))? {
+                Some(sh) => Ok(Precondition {
+                    sh,
+                    msg: extract_string(precondition, "msg").map_err(|e| e.at(idx))?,
+                }),
+                None => Err(InvalidValue::new("", "sh key not found in precondition").at(idx)),
+            },
+            _ => Err(InvalidValue::new("", "precondition must be a string or mapping").at(idx)),
+        })
+        .collect()
+}
+
+fn parse_requires(yml: &Value) -> Result<Vec<String>> {
+    let vars = match yml {
+        Value::Null => return Ok(Vec::new()),
+        Value::Mapping(_) => match yml.get("vars") {
+            None | Some(Value::Null) => return Ok(Vec::new()),
+            Some(Value::Sequence(vars)) => vars,
+            Some(_) => {
+                return Err(InvalidValue::new(
+                    "vars",
+                    "value of vars key must be a list",
+                ))
+            }
+        },
+        _ => return Err(InvalidValue::new("", "requires must be a mapping")),
+    };
+
+    vars.iter()
+        .enumerate()
+        .map(|(idx, var)| match var {
+            Value::String(name) => Ok(name.to_string()),
+            // newer versions of task allow `{name: VAR, enum: [...]}`
+            Value::Mapping(_) => match extract_string(var, "name") {
+                Ok(Some(name)) => Ok(name),
+                Ok(None) => Err(InvalidValue::new(
+                    "",
+                    "name key not found in required variable",
+                )),
+                Err(e) => Err(e),
+            }
+            .map_err(|e| e.at(idx).at("vars")),
+            _ => Err(InvalidValue::new("", "required variables must be strings")
+                .at(idx)
+                .at("vars")),
+        })
+        .collect()
+}
+
+/// Unwraps the value of a key that is only displayed, falling back to its
+/// default and keeping the error as a warning.
+fn lenient<T: Default>(result: Result<T>, warnings: &mut Vec<InvalidValue>) -> T {
+    result.unwrap_or_else(|e| {
+        warnings.push(e);
+        T::default()
+    })
+}
+
+fn parse_prompt(yml: &Value) -> Result<Vec<String>> {
+    match yml {
+        Value::Null => Ok(Vec::new()),
+        Value::String(prompt) => Ok(vec![prompt.to_string()]),
+        Value::Sequence(prompts) => prompts
+            .iter()
+            .enumerate()
+            .map(|(idx, prompt)| match prompt {
+                Value::String(prompt) => Ok(prompt.to_string()),
+                _ => Err(InvalidValue::new("", "prompts must be strings").at(idx)),
+            })
+            .collect(),
+        _ => Err(InvalidValue::new(
+            "",
+            "prompt must be a string or a list of strings",
+        )),
+    }
+}
+
+fn parse_run(yml: Option<&Value>) -> Result<Run> {
+    let run = match yml.and_then(Value::as_str) {
+        None | Some("always") => Run::Always,
+        Some("once") => Run::Once,
+        Some("when_changed") => Run::WhenChanged,
+        Some(other) => {
+            return Err(
+                InvalidValue::new("run", format!("invalid run mode: {}", other))
+                    .with_hint("use one of always, once or when_changed"),
+            )
+        }
+    };
+
+    Ok(run)
+}
+
+pub fn extract_bool(yml: &Value, field: &str, default: bool) -> Result<bool> {
+    match yml {
+        Value::Mapping(v) => {
+            if let Some(value) = v.get(field).and_then(Value::as_bool) {
+                Ok(value)
+            } else {
+                Ok(default)
+            }
+        }
+        _ => Ok(false),
+    }
+}
+
+pub fn extract_string(yml: &Value, field: &str) -> Result<Option<String>> {
+    match yml.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.to_string())),
+        Some(Value::Bool(b)) => Ok(Some(b.to_string())),
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(_) => Err(InvalidValue::new(
+            field,
+            format!("value of {} key must be of type string", field),
+        )),
+    }
+}
+
+pub fn extract_string_list(yml: &Value, field: &str) -> Result<Vec<String>> {
+    match yml.get(field) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![s.to_string()]),
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| match item {
+                Value::String(s) => Ok(s.to_string()),
+                Value::Mapping(_) => {
+                    match extract_string(item, "exclude").map_err(|e| e.at(idx).at(field))? {
+                        Some(exclude) => Ok(format!("!{}", exclude)),
+                        None => Err(InvalidValue::new(
+                            "",
+                            format!("items of {} must be strings", field),
+                        )
+                        .at(idx)
+                        .at(field)),
+                    }
+                }
+                _ => Err(
+                    InvalidValue::new("", format!("items of {} must be strings", field))
+                        .at(idx)
+                        .at(field),
+                ),
+            })
+            .collect(),
+        Some(_) => Err(InvalidValue::new(
+            field,
+            format!("value of {} key must be a list", field),
+        )),
+    }
+}
+
+impl std::fmt::Display for Cmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cmd::Shell {
+                cmd,
+                silent,
+                ignore_error,
+                platforms,
+            } => {
+                let mut options = Vec::new();
+                if *silent {
+                    options.push("silent".to_string());
+                }
+                if *ignore_error {
+                    options.push("ignore_error".to_string());
+                }
+                if !platforms.is_empty() {
+                    options.push(format!("platforms: {}", platforms.join(", ")));
+                }
+                write!(f, "{}{}", cmd, formatted_options(&options))
+            }
+            Cmd::Task { task, vars, silent } => {
+                write!(f, "task: {}{}", task, call_options(vars, *silent))
+            }
+            Cmd::Defer(cmd) => write!(f, "defer: {}", cmd),
+        }
+    }
+}
+
+impl std::fmt::Display for Dep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.task, call_options(&self.vars, self.silent))
+    }
+}
+
+/// The options of a call of another task, like `(vars: A=1, silent)`.
+fn call_options(vars: &[Var], silent: bool) -> String {
+    let mut options = Vec::new();
+    if !vars.is_empty() {
+        let vars: Vec<String> = vars
+            .iter()
+            .map(|var| format!("{}={}", var.name, var.value))
+            .collect();
+        options.push(format!("vars: {}", vars.join(", ")));
+    }
+    if silent {
+        options.push("silent".to_string());
+    }
+    formatted_options(&options)
+}
+
+fn formatted_options(options: &[String]) -> String {
+    if options.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", options.join(", "))
+    }
+}
+
+impl std::fmt::Display for VarValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VarValue::Static(value) => write!(f, "{}", value_to_string(value)),
+            VarValue::Sh(sh) => write!(f, "sh: {}", sh),
+            VarValue::Ref(reference) => write!(f, "ref: {}", reference),
+            VarValue::Evaluated {
+                sh,
+                output: Ok(output),
+            } => write!(f, "{} (evaluated sh: {})", output, sh),
+            VarValue::Evaluated {
+                sh,
+                output: Err(err),
+            } => write!(f, "sh: {} (evaluation failed: {})", sh, err),
+        }
+    }
+}
+
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.to_string(),
+        Value::Sequence(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(value_to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Value::Mapping(mapping) => format!(
+            "{{{}}}",
+            mapping
+                .iter()
+                .map(|(k, v)| format!("{}: {}", value_to_string(k), value_to_string(v)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Value::Tagged(tagged) => value_to_string(&tagged.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str) -> Task {
+        Task {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        let start = task("start:*");
+        assert_eq!(
+            start.wildcard_match("start:web"),
+            Some(vec!["web".to_string()])
+        );
+        assert_eq!(start.wildcard_match("start:"), Some(vec![String::new()]));
+        assert_eq!(start.wildcard_match("stop:web"), None);
+        assert_eq!(task("start").wildcard_match("start"), None);
+    }
+
+    #[test]
+    fn matches_earlier_wildcards_greedily() {
+        let deploy = task("deploy:*:*");
+        assert_eq!(
+            deploy.wildcard_match("deploy:a:b:c"),
+            Some(vec!["a:b".to_string(), "c".to_string()])
+        );
+        assert_eq!(deploy.wildcard_match("deploy:a"), None);
+        assert_eq!(
+            task("*-*").wildcard_match("größe-ä"),
+            Some(vec!["größe".to_string(), "ä".to_string()])
+        );
+    }
+
+    #[test]
+    fn matches_literal_parts() {
+        assert_eq!(match_parts(&["a"], "a"), Some(Vec::new()));
+        assert_eq!(match_parts(&["a"], "ab"), None);
+        assert_eq!(
+            match_parts(&["", ":", ""], "x:y"),
+            Some(vec!["x".to_string(), "y".to_string()])
+        );
+        assert_eq!(match_parts(&["a", "b"], "acb"), Some(vec!["c".to_string()]));
+        assert_eq!(match_parts(&["a", "b"], "acd"), None);
+    }
+}
@@ -0,0 +1,1132 @@
+use serde_yaml::{Mapping, Number, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::task::value_to_string;
+
+/// Upper bound for lengths and counts given to functions and `range`, so a
+/// typo cannot make the preview allocate without limit.
+const MAX_COUNT: usize = 1 << 20;
+
+// A small evaluator for the subset of Go templates used in Taskfiles: variables,
+// pipelines, the common functions of Task and slim-sprig, and if/else/range/with.
+// Actions that cannot be evaluated are kept as they are written.
+
+/// Variables available to a template. Dynamic variables are known to exist but
+/// have no value yet, so actions referencing them are left unresolved.
+#[derive(Clone, Debug, Default)]
+pub struct Scope {
+    pub vars: Mapping,
+    pub dynamic: HashSet<String>,
+    /// Results of the `sh:` variables that were evaluated, by variable name.
+    pub evaluated: HashMap<String, Result<String, String>>,
+}
+
+impl Scope {
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.dynamic.remove(name);
+        self.vars.insert(Value::String(name.to_string()), value);
+    }
+
+    pub fn set_dynamic(&mut self, name: &str) {
+        self.vars.remove(name);
+        self.dynamic.insert(name.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+}
+
+pub fn render(template: &str, scope: &Scope) -> String {
+    if !template.contains("{{") {
+        return template.to_string();
+    }
+
+    match parse(template) {
+        Ok(nodes) => {
+            let root = Value::Mapping(scope.vars.clone());
+            let exec = Exec { scope, root: &root };
+            let mut out = String::new();
+            exec.render_nodes(template, &nodes, &root, &mut out);
+            out
+        }
+        Err(_) => template.to_string(),
+    }
+}
+
+/// Evaluates a single expression such as `.VAR` against the scope.
+pub fn evaluate(expression: &str, scope: &Scope) -> Option<Value> {
+    let pipeline = Parser::new(expression).pipeline().ok()?;
+    let root = Value::Mapping(scope.vars.clone());
+    let exec = Exec { scope, root: &root };
+
+    exec.pipeline(&pipeline, &root).ok()
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Action {
+        pipeline: Pipeline,
+        raw: (usize, usize),
+    },
+    Block {
+        kind: BlockKind,
+        pipeline: Pipeline,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+        raw: (usize, usize),
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockKind {
+    If,
+    Range,
+    With,
+}
+
+type Pipeline = Vec<Command>;
+type Command = Vec<Operand>;
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Field(Vec<String>),
+    Root(Vec<String>),
+    Literal(Value),
+    Function(String),
+    Pipeline(Pipeline),
+}
+
+/// A raw template action between `{{` and `}}`.
+struct Token<'a> {
+    content: &'a str,
+    start: usize,
+    end: usize,
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut parts: Vec<(Option<Token>, String)> = Vec::new();
+    let mut rest = 0;
+    let mut trim_next = false;
+
+    while let Some(offset) = template[rest..].find("{{") {
+        let start = rest + offset;
+        let close = find_action_end(template, start + 2)?;
+        let mut content = &template[start + 2..close];
+        let mut text = &template[rest..start];
+
+        if trim_next {
+            text = text.trim_start();
+        }
+        if let Some(stripped) = content.strip_prefix("- ") {
+            text = text.trim_end();
+            content = stripped;
+        }
+        trim_next = false;
+        if let Some(stripped) = content.strip_suffix(" -") {
+            trim_next = true;
+            content = stripped;
+        }
+
+        parts.push((
+            Some(Token {
+                content: content.trim(),
+                start,
+                end: close + 2,
+            }),
+            text.to_string(),
+        ));
+        rest = close + 2;
+    }
+
+    let mut text = &template[rest..];
+    if trim_next {
+        text = text.trim_start();
+    }
+    parts.push((None, text.to_string()));
+
+    let mut parts = parts.into_iter();
+    let (nodes, end) = parse_nodes(&mut parts)?;
+
+    match end {
+        None => Ok(nodes),
+        Some((keyword, _, _)) => Err(format!("unexpected {}", keyword)),
+    }
+}
+
+fn find_action_end(template: &str, from: usize) -> Result<usize, String> {
+    let bytes = template.as_bytes();
+    let mut i = from;
+    let mut quote: Option<u8> = None;
+
+    while i < bytes.len() {
+        match quote {
+            Some(q) => {
+                if bytes[i] == b'\\' && q == b'"' {
+                    i += 1;
+                } else if bytes[i] == q {
+                    quote = None;
+                }
+            }
+            None => {
+                if bytes[i] == b'"' || bytes[i] == b'`' || bytes[i] == b'\'' {
+                    quote = Some(bytes[i]);
+                } else if bytes[i..].starts_with(b"}}") {
+                    return Ok(i);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    Err("unclosed action".to_string())
+}
+
+/// An `else` or `end` action with its start and end position.
+type Terminator = (String, usize, usize);
+
+type Parts<'a> = std::vec::IntoIter<(Option<Token<'a>>, String)>;
+
+/// Parses nodes until the parts run out or an `else`/`end` action is found,
+/// which is returned together with its position.
+fn parse_nodes<'a>(parts: &mut Parts<'a>) -> Result<(Vec<Node>, Option<Terminator>), String> {
+    let mut nodes = Vec::new();
+
+    while let Some((token, text)) = parts.next() {
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        let token = match token {
+            Some(token) => token,
+            None => break,
+        };
+        let content = token.content;
+
+        if content.starts_with("/*") {
+            continue;
+        }
+        if content == "end" || content == "else" || content.starts_with("else ") {
+            return Ok((nodes, Some((content.to_string(), token.start, token.end))));
+        }
+
+        let kind = if let Some(rest) = content.strip_prefix("if ") {
+            Some((BlockKind::If, rest))
+        } else if let Some(rest) = content.strip_prefix("range ") {
+            Some((BlockKind::Range, rest))
+        } else {
+            content
+                .strip_prefix("with ")
+                .map(|rest| (BlockKind::With, rest))
+        };
+
+        match kind {
+            Some((kind, rest)) => {
+                nodes.push(parse_block(parts, kind, rest, token.start)?);
+            }
+            None => nodes.push(Node::Action {
+                pipeline: Parser::new(content).pipeline()?,
+                raw: (token.start, token.end),
+            }),
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn parse_block<'a>(
+    parts: &mut Parts<'a>,
+    kind: BlockKind,
+    condition: &str,
+    start: usize,
+) -> Result<Node, String> {
+    let pipeline = Parser::new(condition).pipeline()?;
+    let (body, end) = parse_nodes(parts)?;
+
+    let (otherwise, end) = match end {
+        Some((keyword, _, _)) if keyword == "else" => {
+            let (otherwise, end) = parse_nodes(parts)?;
+            (otherwise, end)
+        }
+        // `else if` is a nested if block that shares the outer end
+        Some((keyword, else_start, _)) if keyword.starts_with("else if ") => {
+            let nested = parse_block(parts, BlockKind::If, &keyword[8..], else_start)?;
+            let end = match &nested {
+                Node::Block { raw, .. } => Some(("end".to_string(), raw.1, raw.1)),
+                _ => None,
+            };
+            (vec![nested], end)
+        }
+        end => (Vec::new(), end),
+    };
+
+    match end {
+        Some((keyword, _, end)) if keyword == "end" => Ok(Node::Block {
+            kind,
+            pipeline,
+            body,
+            otherwise,
+            raw: (start, end),
+        }),
+        _ => Err("missing end".to_string()),
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Advances past the characters matching `pred` and returns them.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .char_indices()
+            .find(|(_, c)| !pred(*c))
+            .map(|(idx, _)| idx)
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn pipeline(&mut self) -> Result<Pipeline, String> {
+        let mut pipeline = vec![self.command()?];
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('|') => {
+                    self.pos += 1;
+                    pipeline.push(self.command()?);
+                }
+                None | Some(')') => return Ok(pipeline),
+                Some(c) => return Err(format!("unexpected {}", c)),
+            }
+        }
+    }
+
+    fn command(&mut self) -> Result<Command, String> {
+        let mut command = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                _ => command.push(self.operand()?),
+            }
+        }
+
+        if command.is_empty() {
+            return Err("empty command".to_string());
+        }
+
+        Ok(command)
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        let c = self.peek().ok_or("unexpected end")?;
+
+        match c {
+            '.' => Ok(Operand::Field(self.fields())),
+            '$' => {
+                self.pos += 1;
+                Ok(Operand::Root(self.fields()))
+            }
+            '(' => {
+                self.pos += 1;
+                let pipeline = self.pipeline()?;
+                if self.peek() != Some(')') {
+                    return Err("missing )".to_string());
+                }
+                self.pos += 1;
+                Ok(Operand::Pipeline(pipeline))
+            }
+            '"' | '`' | '\'' => self.string(c),
+            c if c.is_ascii_digit() || c == '-' => {
+                let word = self.word();
+                if let Ok(i) = word.parse::<i64>() {
+                    Ok(Operand::Literal(Value::Number(Number::from(i))))
+                } else if let Ok(f) = word.parse::<f64>() {
+                    Ok(Operand::Literal(Value::Number(Number::from(f))))
+                } else {
+                    Err(format!("invalid number {}", word))
+                }
+            }
+            _ => {
+                let word = self.word();
+                match word.as_str() {
+                    "" => Err(format!("unexpected {}", c)),
+                    "true" => Ok(Operand::Literal(Value::Bool(true))),
+                    "false" => Ok(Operand::Literal(Value::Bool(false))),
+                    "nil" => Ok(Operand::Literal(Value::Null)),
+                    _ => Ok(Operand::Function(word)),
+                }
+            }
+        }
+    }
+
+    fn word(&mut self) -> String {
+        self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+            .to_string()
+    }
+
+    fn fields(&mut self) -> Vec<String> {
+        let mut fields = Vec::new();
+
+        while self.peek() == Some('.') {
+            self.pos += 1;
+            let field = self.take_while(|c| c.is_alphanumeric() || c == '_');
+            if !field.is_empty() {
+                fields.push(field.to_string());
+            }
+        }
+
+        fields
+    }
+
+    fn string(&mut self, quote: char) -> Result<Operand, String> {
+        self.pos += 1;
+        let mut value = String::new();
+
+        loop {
+            let c = self.peek().ok_or("unterminated string")?;
+            self.pos += c.len_utf8();
+
+            match c {
+                c if c == quote => break,
+                '\\' if quote == '"' => {
+                    let escaped = self.peek().ok_or("unterminated string")?;
+                    self.pos += escaped.len_utf8();
+                    value.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => other,
+                    });
+                }
+                c => value.push(c),
+            }
+        }
+
+        Ok(Operand::Literal(Value::String(value)))
+    }
+}
+
+struct Exec<'a> {
+    scope: &'a Scope,
+    root: &'a Value,
+}
+
+type EvalResult = Result<Value, String>;
+
+impl Exec<'_> {
+    fn render_nodes(&self, template: &str, nodes: &[Node], dot: &Value, out: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Action { pipeline, raw } => match self.pipeline(pipeline, dot) {
+                    Ok(value) => out.push_str(&format_value(&value)),
+                    Err(_) => out.push_str(&template[raw.0..raw.1]),
+                },
+                Node::Block {
+                    kind,
+                    pipeline,
+                    body,
+                    otherwise,
+                    raw,
+                } => {
+                    let value = match self.pipeline(pipeline, dot) {
+                        Ok(value) => value,
+                        Err(_) => {
+                            out.push_str(&template[raw.0..raw.1]);
+                            continue;
+                        }
+                    };
+
+                    match kind {
+                        BlockKind::If if truthy(&value) => {
+                            self.render_nodes(template, body, dot, out)
+                        }
+                        BlockKind::With if truthy(&value) => {
+                            self.render_nodes(template, body, &value, out)
+                        }
+                        BlockKind::Range if truthy(&value) => match &value {
+                            Value::Sequence(items) => {
+                                for item in items {
+                                    self.render_nodes(template, body, item, out);
+                                }
+                            }
+                            Value::Mapping(mapping) => {
+                                for (_, item) in mapping {
+                                    self.render_nodes(template, body, item, out);
+                                }
+                            }
+                            Value::Number(_) => match count(&value) {
+                                Ok(n) => {
+                                    for i in 0..n {
+                                        let item = Value::Number(Number::from(i as u64));
+                                        self.render_nodes(template, body, &item, out);
+                                    }
+                                }
+                                Err(_) => out.push_str(&template[raw.0..raw.1]),
+                            },
+                            _ => out.push_str(&template[raw.0..raw.1]),
+                        },
+                        _ => self.render_nodes(template, otherwise, dot, out),
+                    }
+                }
+            }
+        }
+    }
+
+    fn pipeline(&self, pipeline: &Pipeline, dot: &Value) -> EvalResult {
+        let mut piped: Option<Value> = None;
+
+        for command in pipeline {
+            piped = Some(self.command(command, dot, piped)?);
+        }
+
+        piped.ok_or("empty pipeline".to_string())
+    }
+
+    fn command(&self, command: &Command, dot: &Value, piped: Option<Value>) -> EvalResult {
+        match &command[0] {
+            Operand::Function(name) => {
+                let mut args = command[1..]
+                    .iter()
+                    .map(|operand| self.operand(operand, dot))
+                    .collect::<Result<Vec<Value>, String>>()?;
+                args.extend(piped);
+                call(name, args)
+            }
+            operand if command.len() == 1 && piped.is_none() => self.operand(operand, dot),
+            _ => Err("only functions can take arguments".to_string()),
+        }
+    }
+
+    fn operand(&self, operand: &Operand, dot: &Value) -> EvalResult {
+        match operand {
+            Operand::Field(fields) => self.lookup(dot, fields),
+            Operand::Root(fields) => self.lookup(self.root, fields),
+            Operand::Literal(value) => Ok(value.clone()),
+            Operand::Function(name) => call(name, Vec::new()),
+            Operand::Pipeline(pipeline) => self.pipeline(pipeline, dot),
+        }
+    }
+
+    fn lookup(&self, dot: &Value, fields: &[String]) -> EvalResult {
+        if let Some(first) = fields.first() {
+            if std::ptr::eq(dot, self.root) && self.scope.dynamic.contains(first) {
+                return Err(format!("{} is not evaluated", first));
+            }
+        }
+
+        let mut value = dot;
+        for field in fields {
+            value = match value.get(field.as_str()) {
+                Some(value) => value,
+                None => return Ok(Value::Null),
+            };
+        }
+
+        Ok(value.clone())
+    }
+}
+
+/// Formats a value like Go's `%v`, with nil rendered as an empty string like Task does.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Sequence(items) => format!(
+            "[{}]",
+            items.iter().map(format_value).collect::<Vec<_>>().join(" ")
+        ),
+        Value::Mapping(mapping) => format!(
+            "map[{}]",
+            mapping
+                .iter()
+                .map(|(k, v)| format!("{}:{}", format_value(k), format_value(v)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        other => value_to_string(other),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Sequence(items) => !items.is_empty(),
+        Value::Mapping(mapping) => !mapping.is_empty(),
+        Value::Tagged(tagged) => truthy(&tagged.value),
+    }
+}
+
+fn string(value: &Value) -> String {
+    format_value(value)
+}
+
+fn number(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(n) => n.as_f64().ok_or("invalid number".to_string()),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| format!("{} is not a number", s)),
+        Value::Null => Ok(0.0),
+        _ => Err("not a number".to_string()),
+    }
+}
+
+/// A number used as a length or count, which has to be a non-negative integer
+/// of at most `MAX_COUNT`.
+fn count(value: &Value) -> Result<usize, String> {
+    let n = number(value)?;
+    if n < 0.0 || n.fract() != 0.0 || n > MAX_COUNT as f64 {
+        return Err(format!("{} is not a count from 0 to {}", n, MAX_COUNT));
+    }
+
+    Ok(n as usize)
+}
+
+fn to_number(f: f64) -> Value {
+    if f.fract() == 0.0 {
+        Value::Number(Number::from(f as i64))
+    } else {
+        Value::Number(Number::from(f))
+    }
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(items) => items.iter().map(string).collect(),
+        Value::Null => Vec::new(),
+        other => vec![string(other)],
+    }
+}
+
+fn list(items: Vec<String>) -> Value {
+    Value::Sequence(items.into_iter().map(Value::String).collect())
+}
+
+fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Number(_), _) | (_, Value::Number(_)) => {
+            number(a).ok()?.partial_cmp(&number(b).ok()?)
+        }
+        _ => Some(string(a).cmp(&string(b))),
+    }
+}
+
+fn call(name: &str, args: Vec<Value>) -> EvalResult {
+    let arg = |i: usize| args.get(i).cloned().unwrap_or(Value::Null);
+    let s = |i: usize| string(&arg(i));
+    let expect = |n: usize| {
+        if args.len() < n {
+            Err(format!("{} expects {} arguments", name, n))
+        } else {
+            Ok(())
+        }
+    };
+
+    let value = match name {
+        // Task
+        "OS" => Value::String(go_os().to_string()),
+        "ARCH" => Value::String(go_arch().to_string()),
+        "numCPU" => Value::Number(Number::from(
+            std::thread::available_parallelism().map_or(1, |n| n.get() as u64),
+        )),
+        "exeExt" => Value::String(if cfg!(windows) { ".exe" } else { "" }.to_string()),
+        "fromSlash" => {
+            expect(1)?;
+            Value::String(s(0).replace('/', std::path::MAIN_SEPARATOR_STR))
+        }
+        "toSlash" => {
+            expect(1)?;
+            Value::String(s(0).replace(std::path::MAIN_SEPARATOR, "/"))
+        }
+        "joinPath" => Value::String(
+            args.iter()
+                .map(string)
+                .fold(PathBuf::new(), |path, part| path.join(part))
+                .display()
+                .to_string(),
+        ),
+        "relPath" => {
+            expect(2)?;
+            let target = s(1);
+            let base = s(0);
+            Value::String(
+                Path::new(&target)
+                    .strip_prefix(&base)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or(target),
+            )
+        }
+        "catLines" => {
+            expect(1)?;
+            Value::String(s(0).replace("\r\n", " ").replace('\n', " "))
+        }
+        "splitLines" => {
+            expect(1)?;
+            list(s(0).lines().map(str::to_string).collect())
+        }
+        "shellQuote" | "q" => {
+            expect(1)?;
+            Value::String(shell_quote(&s(0)))
+        }
+        "splitArgs" => {
+            expect(1)?;
+            list(s(0).split_whitespace().map(str::to_string).collect())
+        }
+        // strings
+        "trim" => {
+            expect(1)?;
+            Value::String(s(0).trim().to_string())
+        }
+        "trimAll" => {
+            expect(2)?;
+            let cutset: Vec<char> = s(0).chars().collect();
+            Value::String(s(1).trim_matches(cutset.as_slice()).to_string())
+        }
+        "trimPrefix" => {
+            expect(2)?;
+            let value = s(1);
+            Value::String(value.strip_prefix(&s(0)).unwrap_or(&value).to_string())
+        }
+        "trimSuffix" => {
+            expect(2)?;
+            let value = s(1);
+            Value::String(value.strip_suffix(&s(0)).unwrap_or(&value).to_string())
+        }
+        "upper" => {
+            expect(1)?;
+            Value::String(s(0).to_uppercase())
+        }
+        "lower" => {
+            expect(1)?;
+            Value::String(s(0).to_lowercase())
+        }
+        "title" => {
+            expect(1)?;
+            Value::String(
+                s(0).split(' ')
+                    .map(|word| {
+                        let mut chars = word.chars();
+                        match chars.next() {
+                            Some(first) => first.to_uppercase().chain(chars).collect(),
+                            None => String::new(),
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            )
+        }
+        "replace" => {
+            expect(3)?;
+            Value::String(s(2).replace(&s(0), &s(1)))
+        }
+        "repeat" => {
+            expect(2)?;
+            let times = count(&arg(0))?;
+            let value = s(1);
+            if value.len().saturating_mul(times) > MAX_COUNT {
+                return Err(format!("repeat result is longer than {}", MAX_COUNT));
+            }
+            Value::String(value.repeat(times))
+        }
+        "contains" => {
+            expect(2)?;
+            Value::Bool(s(1).contains(&s(0)))
+        }
+        "hasPrefix" => {
+            expect(2)?;
+            Value::Bool(s(1).starts_with(&s(0)))
+        }
+        "hasSuffix" => {
+            expect(2)?;
+            Value::Bool(s(1).ends_with(&s(0)))
+        }
+        "quote" => Value::String(
+            args.iter()
+                .map(|arg| format!("{:?}", string(arg)))
+                .collect::<Vec<String>>()
+                .join(" "),
+        ),
+        "squote" => Value::String(
+            args.iter()
+                .map(|arg| format!("'{}'", string(arg)))
+                .collect::<Vec<String>>()
+                .join(" "),
+        ),
+        "cat" => Value::String(
+            args.iter()
+                .filter(|arg| !arg.is_null())
+                .map(string)
+                .collect::<Vec<String>>()
+                .join(" "),
+        ),
+        "indent" | "nindent" => {
+            expect(2)?;
+            let pad = " ".repeat(count(&arg(0))?);
+            let indented = s(1)
+                .lines()
+                .map(|line| format!("{}{}", pad, line))
+                .collect::<Vec<String>>()
+                .join("\n");
+            if name == "nindent" {
+                Value::String(format!("\n{}", indented))
+            } else {
+                Value::String(indented)
+            }
+        }
+        "toString" => {
+            expect(1)?;
+            Value::String(s(0))
+        }
+        "printf" => {
+            expect(1)?;
+            Value::String(printf(&s(0), &args[1..]))
+        }
+        "print" => Value::String(args.iter().map(string).collect::<Vec<String>>().concat()),
+        "println" => {
+            Value::String(args.iter().map(string).collect::<Vec<String>>().join(" ") + "\n")
+        }
+        // lists
+        "list" => Value::Sequence(args.clone()),
+        "join" => {
+            expect(2)?;
+            Value::String(strings(&arg(1)).join(&s(0)))
+        }
+        "splitList" => {
+            expect(2)?;
+            list(s(1).split(&s(0)).map(str::to_string).collect())
+        }
+        "first" => {
+            expect(1)?;
+            match arg(0) {
+                Value::Sequence(items) => items.first().cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            }
+        }
+        "last" => {
+            expect(1)?;
+            match arg(0) {
+                Value::Sequence(items) => items.last().cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            }
+        }
+        "len" => {
+            expect(1)?;
+            let len = match arg(0) {
+                Value::Sequence(items) => items.len(),
+                Value::Mapping(mapping) => mapping.len(),
+                other => string(&other).chars().count(),
+            };
+            Value::Number(Number::from(len as u64))
+        }
+        "index" => {
+            expect(2)?;
+            args[1..]
+                .iter()
+                .fold(arg(0), |value, key| match (&value, key) {
+                    (Value::Sequence(items), key) => count(key)
+                        .ok()
+                        .and_then(|i| items.get(i).cloned())
+                        .unwrap_or(Value::Null),
+                    (Value::Mapping(mapping), key) => mapping
+                        .get(string(key).as_str())
+                        .cloned()
+                        .unwrap_or(Value::Null),
+                    _ => Value::Null,
+                })
+        }
+        // defaults and logic
+        "default" => {
+            expect(1)?;
+            if truthy(&arg(1)) {
+                arg(1)
+            } else {
+                arg(0)
+            }
+        }
+        "empty" => {
+            expect(1)?;
+            Value::Bool(!truthy(&arg(0)))
+        }
+        "coalesce" => args
+            .iter()
+            .find(|arg| truthy(arg))
+            .cloned()
+            .unwrap_or(Value::Null),
+        "ternary" => {
+            expect(3)?;
+            if truthy(&arg(2)) {
+                arg(0)
+            } else {
+                arg(1)
+            }
+        }
+        "not" => {
+            expect(1)?;
+            Value::Bool(!truthy(&arg(0)))
+        }
+        "and" => {
+            expect(1)?;
+            args.iter()
+                .find(|arg| !truthy(arg))
+                .cloned()
+                .unwrap_or_else(|| arg(args.len() - 1))
+        }
+        "or" => {
+            expect(1)?;
+            args.iter()
+                .find(|arg| truthy(arg))
+                .cloned()
+                .unwrap_or_else(|| arg(args.len() - 1))
+        }
+        "eq" => {
+            expect(2)?;
+            Value::Bool(
+                args[1..]
+                    .iter()
+                    .any(|other| compare(&arg(0), other) == Some(std::cmp::Ordering::Equal)),
+            )
+        }
+        "ne" => {
+            expect(2)?;
+            Value::Bool(compare(&arg(0), &arg(1)) != Some(std::cmp::Ordering::Equal))
+        }
+        "lt" | "le" | "gt" | "ge" => {
+            expect(2)?;
+            let ordering = compare(&arg(0), &arg(1)).ok_or("values are not comparable")?;
+            Value::Bool(match name {
+                "lt" => ordering.is_lt(),
+                "le" => ordering.is_le(),
+                "gt" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        // math
+        "add" => to_number(args.iter().map(number).sum::<Result<f64, String>>()?),
+        "sub" => {
+            expect(2)?;
+            to_number(number(&arg(0))? - number(&arg(1))?)
+        }
+        "mul" => to_number(args.iter().map(number).product::<Result<f64, String>>()?),
+        "div" => {
+            expect(2)?;
+            let divisor = number(&arg(1))?;
+            if divisor == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            to_number((number(&arg(0))? / divisor).trunc())
+        }
+        // environment and paths
+        "env" => {
+            expect(1)?;
+            Value::String(std::env::var(s(0)).unwrap_or_default())
+        }
+        "base" => {
+            expect(1)?;
+            Value::String(
+                Path::new(&s(0))
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            )
+        }
+        "dir" => {
+            expect(1)?;
+            Value::String(
+                Path::new(&s(0))
+                    .parent()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default(),
+            )
+        }
+        "ext" => {
+            expect(1)?;
+            Value::String(
+                Path::new(&s(0))
+                    .extension()
+                    .map(|ext| format!(".{}", ext.to_string_lossy()))
+                    .unwrap_or_default(),
+            )
+        }
+        _ => return Err(format!("function {} not supported", name)),
+    };
+
+    Ok(value)
+}
+
+pub fn go_os() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    }
+}
+
+pub fn go_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "386",
+        "aarch64" => "arm64",
+        "powerpc64" => "ppc64",
+        arch => arch,
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_./=:,+@%".contains(c))
+    {
+        return value.to_string();
+    }
+
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn printf(format: &str, args: &[Value]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('q') => out.push_str(&format!(
+                "{:?}",
+                string(args.next().unwrap_or(&Value::Null))
+            )),
+            Some('d') => out.push_str(
+                &number(args.next().unwrap_or(&Value::Null))
+                    .map(|n| (n as i64).to_string())
+                    .unwrap_or_default(),
+            ),
+            Some(_) => out.push_str(&string(args.next().unwrap_or(&Value::Null))),
+            None => out.push('%'),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(vars: &[(&str, &str)]) -> Scope {
+        let mut scope = Scope::default();
+        for (name, value) in vars {
+            scope.set(name, Value::String(value.to_string()));
+        }
+        scope
+    }
+
+    #[test]
+    fn renders_variables() {
+        let scope = scope(&[("NAME", "world")]);
+        assert_eq!(render("hello {{.NAME}}", &scope), "hello world");
+        assert_eq!(render("{{ .NAME | upper }}", &scope), "WORLD");
+        assert_eq!(render("no actions", &scope), "no actions");
+    }
+
+    #[test]
+    fn rejects_invalid_counts() {
+        let scope = scope(&[]);
+        assert_eq!(render("{{repeat 3 \"ab\"}}", &scope), "ababab");
+        assert_eq!(render("{{indent 2 \"a\"}}", &scope), "  a");
+        assert_eq!(render("{{range 3}}{{.}}{{end}}", &scope), "012");
+        for template in [
+            "{{repeat 100000000000000 \"x\"}}",
+            "{{repeat -1 \"x\"}}",
+            "{{indent -2 \"a\"}}",
+            "{{range 100000000000000}}x{{end}}",
+        ] {
+            assert_eq!(render(template, &scope), template);
+        }
+    }
+
+    #[test]
+    fn handles_non_ascii_input() {
+        let scope = scope(&[("GRÜSSE", "hallo"), ("X", "x")]);
+        assert_eq!(render("echo {{.GRÜSSE}}", &scope), "echo hallo");
+        assert_eq!(render("a{{/* größe */}}b", &scope), "ab");
+        assert_eq!(render("{{\u{a0}.X}}", &scope), "x");
+        assert_eq!(render("ä {{.X}} ö", &scope), "ä x ö");
+        assert_eq!(render("{{ \"größe\" }}", &scope), "größe");
+        assert_eq!(render("{{.ÜNBEKANNT}}", &scope), "");
+    }
+
+    #[test]
+    fn trims_whitespace_around_markers() {
+        let scope = scope(&[("X", "x")]);
+        assert_eq!(render("a  {{- .X -}}  b", &scope), "axb");
+        assert_eq!(render("a  {{- .X}}  b", &scope), "ax  b");
+        assert_eq!(render("a  {{.X -}}\n  b", &scope), "a  xb");
+    }
+
+    #[test]
+    fn renders_blocks() {
+        let scope = scope(&[("OS", "linux")]);
+        let template =
+            r#"{{if eq .OS "windows"}}win{{else if eq .OS "linux"}}lin{{else}}other{{end}}"#;
+        assert_eq!(render(template, &scope), "lin");
+        assert_eq!(
+            render(
+                r#"{{if eq .OS "mac"}}mac{{else if eq .OS "bsd"}}bsd{{else}}other{{end}}!"#,
+                &scope
+            ),
+            "other!"
+        );
+        assert_eq!(render("{{with .OS}}[{{.}}]{{end}}", &scope), "[linux]");
+        assert_eq!(render("{{range 3}}{{.}}{{end}}", &scope), "012");
+    }
+
+    #[test]
+    fn keeps_unresolvable_actions() {
+        let mut scope = scope(&[]);
+        scope.set_dynamic("REV");
+        assert_eq!(render("git {{.REV}}", &scope), "git {{.REV}}");
+        assert_eq!(render("{{if .REV}}a{{end}}", &scope), "{{if .REV}}a{{end}}");
+        assert_eq!(render("{{nosuchfunc .X}}", &scope), "{{nosuchfunc .X}}");
+        // templates that do not parse are returned unchanged
+        assert_eq!(render("{{.X", &scope), "{{.X");
+        assert_eq!(render("{{if .X}}a", &scope), "{{if .X}}a");
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let scope = scope(&[("GRÖSSE", "10")]);
+        assert_eq!(
+            evaluate(".GRÖSSE", &scope),
+            Some(Value::String("10".to_string()))
+        );
+        assert_eq!(evaluate("(", &scope), None);
+    }
+}
@@ -0,0 +1,185 @@
+use serde_yaml::Value;
+
+use super::error::InvalidValue;
+use super::task::value_to_string;
+
+// https://taskfile.dev/reference/schema/
+const TASKFILE_KEYS: [&str; 13] = [
+    "version", "output", "method", "includes", "vars", "env", "tasks", "silent", "dotenv", "run",
+    "interval", "set", "shopt",
+];
+
+const INCLUDE_KEYS: [&str; 9] = [
+    "taskfile", "dir", "optional", "internal", "aliases", "vars", "flatten", "excludes", "checksum",
+];
+
+const TASK_KEYS: [&str; 30] = [
+    "cmds",
+    "cmd",
+    "deps",
+    "label",
+    "desc",
+    "prompt",
+    "summary",
+    "aliases",
+    "sources",
+    "generates",
+    "status",
+    "preconditions",
+    "dir",
+    "vars",
+    "env",
+    "dotenv",
+    "silent",
+    "interactive",
+    "internal",
+    "method",
+    "prefix",
+    "ignore_error",
+    "run",
+    "platforms",
+    "set",
+    "shopt",
+    "requires",
+    "watch",
+    "output",
+    "failfast",
+];
+
+/// Checks the `version` key. Version 2 Taskfiles only lead to a warning, as
+/// most of them still work, any other version than 3 is rejected.
+pub fn check_version(yml: &Value) -> Result<Option<InvalidValue>, InvalidValue> {
+    let version = match yml.get("version") {
+        None | Some(Value::Null) => {
+            return Ok(Some(
+                InvalidValue::new("", "no version key found")
+                    .with_hint("add `version: '3'` to the top of the Taskfile"),
+            ))
+        }
+        Some(version) => value_to_string(version),
+    };
+
+    match version.split('.').next().unwrap_or_default() {
+        "3" => Ok(None),
+        "2" => Ok(Some(
+            InvalidValue::new(
+                "version",
+                format!("version {} Taskfiles are deprecated", version),
+            )
+            .with_hint("see https://taskfile.dev/deprecations/version-2-schema/"),
+        )),
+        _ => Err(
+            InvalidValue::new("version", format!("unsupported version: {}", version))
+                .with_hint("only version 3 Taskfiles are supported"),
+        ),
+    }
+}
+
+/// Finds keys of the Taskfile, its includes and tasks that are not part of
+/// the schema, most likely typos.
+pub fn unknown_keys(yml: &Value) -> Vec<InvalidValue> {
+    let mut unknown = check_keys(yml, &TASKFILE_KEYS, "Taskfile");
+
+    if let Some(includes) = yml.get("includes").and_then(Value::as_mapping) {
+        for (name, include) in includes {
+            unknown.extend(
+                check_keys(include, &INCLUDE_KEYS, "include")
+                    .into_iter()
+                    .map(|e| e.at(value_to_string(name)).at("includes")),
+            );
+        }
+    }
+
+    if let Some(tasks) = yml.get("tasks").and_then(Value::as_mapping) {
+        for (name, task) in tasks {
+            unknown.extend(
+                check_keys(task, &TASK_KEYS, "task")
+                    .into_iter()
+                    .map(|e| e.at(value_to_string(name)).at("tasks")),
+            );
+        }
+    }
+
+    unknown
+}
+
+fn check_keys(yml: &Value, known: &[&str], kind: &str) -> Vec<InvalidValue> {
+    let mapping = match yml.as_mapping() {
+        Some(mapping) => mapping,
+        None => return Vec::new(),
+    };
+
+    mapping
+        .keys()
+        .map(value_to_string)
+        .filter(|key| !known.contains(&key.as_str()))
+        .map(|key| {
+            let warning = InvalidValue::new(&key, format!("unknown {} key: {}", kind, key));
+            match suggestion(&key, known) {
+                Some(known_key) => warning.with_hint(format!("did you mean `{}`?", known_key)),
+                None => warning,
+            }
+        })
+        .collect()
+}
+
+/// Returns the known key closest to `key`, if it is close enough to be a typo.
+fn suggestion<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|known_key| (distance(key, known_key), *known_key))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known_key)| known_key)
+}
+
+/// Optimal string alignment distance, which counts swapped letters as one edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(distance("cmds", "cmds"), 0);
+        assert_eq!(distance("", "deps"), 4);
+        assert_eq!(distance("cmd", "cmds"), 1);
+        assert_eq!(distance("dpes", "deps"), 1);
+        assert_eq!(distance("sorces", "sources"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("größe", "grösse"), 2);
+    }
+
+    #[test]
+    fn suggests_close_keys() {
+        assert_eq!(suggestion("dpes", &TASK_KEYS), Some("deps"));
+        assert_eq!(suggestion("prefix", &TASKFILE_KEYS), None);
+        assert_eq!(suggestion("completely", &TASK_KEYS), None);
+    }
+}
@@ -0,0 +1,226 @@
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+
+use super::config::Taskfile;
+use super::shell::ShellVars;
+use super::task::{Cmd, Dep, Precondition, Task, Var, VarValue};
+use super::template::{self, Scope};
+
+/// Builds the variables visible to a task, in the order Task applies them:
+/// environment, special variables, Taskfile, include and task variables.
+/// `sh:` variables are only evaluated if `shell` is given.
+pub fn task_scope(taskfile: &Taskfile, task: &Task, shell: Option<&mut ShellVars>) -> Scope {
+    call_scope(taskfile, task, &[], shell)
+}
+
+/// Like `task_scope`, with `matches` being the parts of the name matched by the
+/// wildcards of the task, set as `MATCH`.
+fn call_scope(
+    taskfile: &Taskfile,
+    task: &Task,
+    matches: &[String],
+    mut shell: Option<&mut ShellVars>,
+) -> Scope {
+    let mut scope = Scope::default();
+
+    for (name, value) in std::env::vars() {
+        scope.set(&name, Value::String(value));
+    }
+
+    // https://taskfile.dev/reference/templating/#special-variables
+    let root_dir = dir_of(&taskfile.path);
+    let taskfile_dir = dir_of(&task.taskfile);
+    let user_working_dir = std::env::current_dir().unwrap_or_default();
+    let specials = [
+        ("CLI_ARGS", String::new()),
+        ("ROOT_TASKFILE", taskfile.path.display().to_string()),
+        ("ROOT_DIR", root_dir.display().to_string()),
+        ("TASKFILE", task.taskfile.display().to_string()),
+        ("TASKFILE_DIR", taskfile_dir.display().to_string()),
+        ("USER_WORKING_DIR", user_working_dir.display().to_string()),
+        ("TASK", task.name.clone()),
+        ("ALIAS", task.name.clone()),
+        ("TASK_EXE", "task".to_string()),
+    ];
+    for (name, value) in specials {
+        scope.set(name, Value::String(value));
+    }
+    for name in ["CLI_FORCE", "CLI_SILENT", "CLI_VERBOSE", "CLI_OFFLINE"] {
+        scope.set(name, Value::Bool(false));
+    }
+    if !matches.is_empty() {
+        let matches = matches.iter().cloned().map(Value::String).collect();
+        scope.set("MATCH", Value::Sequence(matches));
+    }
+
+    for var in taskfile.vars.iter().chain(task.include_vars.iter()) {
+        apply_var(&mut scope, var, shell.as_deref_mut(), &root_dir);
+    }
+
+    let var_dir = match &task.dir {
+        Some(dir) => root_dir.join(template::render(dir, &scope)),
+        None => root_dir.clone(),
+    };
+    for var in task.vars.iter() {
+        apply_var(&mut scope, var, shell.as_deref_mut(), &var_dir);
+    }
+
+    // like `task`, tasks run relative to the root Taskfile unless a dir is set
+    let task_dir = match &task.dir {
+        Some(dir) => root_dir.join(template::render(dir, &scope)),
+        None => root_dir,
+    };
+    scope.set("TASK_DIR", Value::String(task_dir.display().to_string()));
+
+    scope
+}
+
+fn dir_of(path: &Path) -> PathBuf {
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+pub fn apply_var(scope: &mut Scope, var: &Var, shell: Option<&mut ShellVars>, dir: &Path) {
+    match &var.value {
+        VarValue::Static(value) => {
+            let value = render_value(value, scope);
+            scope.set(&var.name, value);
+        }
+        VarValue::Sh(sh) | VarValue::Evaluated { sh, .. } => match shell {
+            Some(shell) => {
+                let output = shell.evaluate(&template::render(sh, scope), dir);
+                match &output {
+                    Ok(value) => scope.set(&var.name, Value::String(value.clone())),
+                    Err(_) => scope.set_dynamic(&var.name),
+                }
+                scope.evaluated.insert(var.name.clone(), output);
+            }
+            None => scope.set_dynamic(&var.name),
+        },
+        VarValue::Ref(reference) => match template::evaluate(reference, scope) {
+            Some(value) => scope.set(&var.name, value),
+            None => scope.set_dynamic(&var.name),
+        },
+    }
+}
+
+fn render_value(value: &Value, scope: &Scope) -> Value {
+    match value {
+        Value::String(s) => Value::String(template::render(s, scope)),
+        Value::Sequence(items) => {
+            Value::Sequence(items.iter().map(|item| render_value(item, scope)).collect())
+        }
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .iter()
+                .map(|(k, v)| (k.clone(), render_value(v, scope)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Returns a copy of the task with all templates resolved as far as possible.
+pub fn resolve_task(taskfile: &Taskfile, task: &Task, shell: Option<&mut ShellVars>) -> Task {
+    resolve_in(task, &task_scope(taskfile, task, shell))
+}
+
+/// Resolves a task as called by `name`, which may match a wildcard task.
+pub fn resolve_call(taskfile: &Taskfile, task: &Task, name: &str) -> Task {
+    let matches = task.wildcard_match(name).unwrap_or_default();
+    resolve_in(task, &call_scope(taskfile, task, &matches, None))
+}
+
+fn resolve_in(task: &Task, scope: &Scope) -> Task {
+    let render = |s: &String| template::render(s, scope);
+    let render_opt = |s: &Option<String>| s.as_ref().map(render);
+
+    Task {
+        desc: render_opt(&task.desc),
+        summary: render_opt(&task.summary),
+        cmds: task
+            .cmds
+            .iter()
+            .map(|cmd| resolve_cmd(cmd, scope))
+            .collect(),
+        deps: task
+            .deps
+            .iter()
+            .map(|dep| Dep {
+                task: render(&dep.task),
+                vars: resolve_vars(&dep.vars, scope),
+                silent: dep.silent,
+            })
+            .collect(),
+        vars: resolve_vars(&task.vars, scope),
+        env: resolve_vars(&task.env, scope),
+        dir: scope
+            .get("TASK_DIR")
+            .filter(|_| task.dir.is_some())
+            .map(template::format_value),
+        preconditions: task
+            .preconditions
+            .iter()
+            .map(|precondition| Precondition {
+                sh: render(&precondition.sh),
+                msg: render_opt(&precondition.msg),
+            })
+            .collect(),
+        sources: task.sources.iter().map(render).collect(),
+        generates: task.generates.iter().map(render).collect(),
+        prompt: task.prompt.iter().map(render).collect(),
+        label: render_opt(&task.label),
+        ..task.clone()
+    }
+}
+
+fn resolve_cmd(cmd: &Cmd, scope: &Scope) -> Cmd {
+    match cmd {
+        Cmd::Shell {
+            cmd,
+            silent,
+            ignore_error,
+            platforms,
+        } => Cmd::Shell {
+            cmd: template::render(cmd, scope),
+            silent: *silent,
+            ignore_error: *ignore_error,
+            platforms: platforms.clone(),
+        },
+        Cmd::Task { task, vars, silent } => Cmd::Task {
+            task: template::render(task, scope),
+            vars: resolve_vars(vars, scope),
+            silent: *silent,
+        },
+        Cmd::Defer(cmd) => Cmd::Defer(Box::new(resolve_cmd(cmd, scope))),
+    }
+}
+
+/// Resolves variable definitions for display. Dynamic variables are kept as they
+/// are, unless they were evaluated.
+fn resolve_vars(vars: &[Var], scope: &Scope) -> Vec<Var> {
+    vars.iter()
+        .map(|var| {
+            let value = match &var.value {
+                VarValue::Static(value) => VarValue::Static(render_value(value, scope)),
+                VarValue::Ref(reference) => match template::evaluate(reference, scope) {
+                    Some(value) => VarValue::Static(value),
+                    None => var.value.clone(),
+                },
+                VarValue::Sh(sh) | VarValue::Evaluated { sh, .. } => {
+                    match scope.evaluated.get(&var.name) {
+                        Some(output) => VarValue::Evaluated {
+                            sh: sh.clone(),
+                            output: output.clone(),
+                        },
+                        None => var.value.clone(),
+                    }
+                }
+            };
+
+            Var {
+                name: var.name.clone(),
+                value,
+            }
+        })
+        .collect()
+}
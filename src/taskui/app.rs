@@ -1,33 +1,402 @@
-use crate::taskfile::config::Task;
+use crate::taskfile::{
+    command::{split_args, Invocation},
+    config::Taskfile,
+    dotenv::task_environment,
+    error::ParseError,
+    fingerprint::{self, Matches, State},
+    git,
+    graph::{dependency_tree, lookup, triggered, used_by, Call, Node},
+    shell::ShellVars,
+    task::{value_to_string, Task},
+    variables::{resolve_call, resolve_task, task_scope},
+};
 use ratatui::widgets::ListState;
-use std::usize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
 
+use super::config::OtherPlatforms;
+use super::history::History;
 use super::Config;
 
 pub struct App {
     pub cfg: Config,
+    pub taskfile: Taskfile,
     pub tasks: StatefulList,
     pub search: String,
     pub input_mode: InputMode,
     pub should_quit: bool,
-    pub task_to_exec: Option<Task>,
+    pub task_to_exec: Option<Invocation>,
+    pub preview_resolved: bool,
+    pub evaluate_sh: bool,
+    pub shell_vars: ShellVars,
+    /// Receives the `sh:` variables of the selected task once they are
+    /// evaluated in the background, as their commands can take a while.
+    shell_vars_rx: Option<Receiver<ShellVars>>,
+    /// Set if the Taskfile could not be loaded, the TUI then only shows the error.
+    pub load_error: Option<ParseError>,
+    /// Up-to-date state of the listed tasks that have `sources`.
+    pub states: HashMap<String, State>,
+    /// Receives the states while they are computed in the background, as
+    /// hashing the `sources` of every task can take a while.
+    states_rx: Option<Receiver<(String, State)>>,
+    /// Files matched by globs shown in the preview, by directory and globs.
+    pub matches: HashMap<(PathBuf, Vec<String>), Matches>,
+    /// Names of the tasks whose `sources` contain files changed in the git
+    /// working tree, `None` if the list is not filtered by them.
+    pub affected: Option<Result<HashSet<String>, String>>,
+    /// Values of the variables to pass to the selected task.
+    pub form: Option<VarForm>,
+    /// Asks for the `CLI_ARGS` of a task that uses them.
+    pub args_prompt: Option<ArgsPrompt>,
+    pub history: History,
+    /// Asks to confirm the `prompt` of a task before running it.
+    pub confirmation: Option<Confirmation>,
+    /// Asks for the parts of a wildcard task name.
+    pub wildcard: Option<WildcardPrompt>,
+    /// Dependency tree of the selected task, built when the view opens.
+    pub dependencies: Option<Node>,
+    /// Tasks triggering the selected task, found when the view opens.
+    pub used_by: Option<Vec<(Task, Call)>>,
 }
 
 impl App {
-    pub fn new(cfg: Config, tasks: Vec<Task>) -> App {
-        let tasks = tasks
-            .into_iter()
+    pub fn new(cfg: Config, taskfile: Taskfile) -> App {
+        let tasks: Vec<Task> = taskfile
+            .tasks
+            .iter()
             .filter(|task| !task.internal || cfg.list_internal)
+            .filter(|task| cfg.other_platforms != OtherPlatforms::Hide || task.matches_platform())
+            .cloned()
             .collect();
+        let states_rx = Some(spawn_states(&taskfile, &tasks));
 
         App {
-            cfg,
+            taskfile,
             tasks: StatefulList::with_items(tasks),
             search: String::new(),
             input_mode: InputMode::Select,
             should_quit: false,
             task_to_exec: None,
+            preview_resolved: false,
+            evaluate_sh: false,
+            shell_vars: ShellVars::new(cfg.sh_timeout),
+            shell_vars_rx: None,
+            load_error: None,
+            states: HashMap::new(),
+            states_rx,
+            matches: HashMap::new(),
+            affected: None,
+            form: None,
+            args_prompt: None,
+            history: History::load(),
+            confirmation: None,
+            wildcard: None,
+            dependencies: None,
+            used_by: None,
+            cfg,
+        }
+    }
+
+    /// Collects the task states and `sh:` variables computed in the
+    /// background so far.
+    pub fn tick(&mut self) {
+        if let Some(rx) = &self.shell_vars_rx {
+            match rx.try_recv() {
+                Ok(shell_vars) => {
+                    self.shell_vars = shell_vars;
+                    self.shell_vars_rx = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.shell_vars_rx = None,
+            }
+        }
+
+        let Some(rx) = &self.states_rx else {
+            return;
+        };
+
+        loop {
+            match rx.try_recv() {
+                Ok((name, state)) => _ = self.states.insert(name, state),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.states_rx = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Whether the `sh:` variables of the selected task are still being
+    /// evaluated, they are only shown once all of them are done.
+    pub fn evaluating_sh(&self) -> bool {
+        self.evaluate_sh && self.shell_vars_rx.is_some()
+    }
+
+    pub fn toggle_evaluate_sh(&mut self) {
+        self.evaluate_sh = !self.evaluate_sh;
+        self.spawn_shell_vars();
+    }
+
+    /// Evaluates the `sh:` variables of the selected task in the background,
+    /// if they are shown.
+    fn spawn_shell_vars(&mut self) {
+        let Some(task) = self.tasks.get_selected() else {
+            return;
+        };
+        if !self.evaluate_sh {
+            return;
         }
+
+        let (tx, rx) = mpsc::channel();
+        let taskfile = self.taskfile.clone();
+        // the cached results are moved to the thread and come back with the new ones
+        let mut shell_vars =
+            std::mem::replace(&mut self.shell_vars, ShellVars::new(self.cfg.sh_timeout));
+
+        thread::spawn(move || {
+            resolve_task(&taskfile, &task, Some(&mut shell_vars));
+            task_environment(&taskfile, &task, Some(&mut shell_vars));
+            _ = tx.send(shell_vars);
+        });
+
+        self.shell_vars_rx = Some(rx);
+    }
+
+    /// Whether the state of the task is still being computed.
+    pub fn checking_state(&self, task: &Task) -> bool {
+        self.states_rx.is_some()
+            && !task.sources.is_empty()
+            && !self.states.contains_key(&task.name)
+    }
+
+    pub fn search(&mut self, query: String) {
+        self.search = query;
+        self.filter();
+    }
+
+    /// Filters the task list by the search and, if enabled, the affected tasks.
+    pub fn filter(&mut self) {
+        let affected = match &self.affected {
+            Some(Ok(names)) => Some(names),
+            _ => None,
+        };
+        self.tasks.filter(&self.search, affected);
+    }
+
+    pub fn toggle_affected(&mut self) {
+        self.affected = match self.affected {
+            Some(_) => None,
+            None => Some(self.affected_tasks()),
+        };
+        self.filter();
+    }
+
+    fn affected_tasks(&self) -> Result<HashSet<String>, String> {
+        let root_dir = self.taskfile.path.parent().unwrap_or(Path::new(""));
+        let changed = git::changed_files(root_dir)?;
+
+        let affected = self
+            .taskfile
+            .tasks
+            .iter()
+            .filter(|task| !task.sources.is_empty())
+            .filter(|task| {
+                let task = resolve_task(&self.taskfile, task, None);
+                let dir = fingerprint::task_dir(&self.taskfile, &task);
+                changed
+                    .iter()
+                    .any(|path| fingerprint::is_matched(&dir, &task.sources, path))
+            })
+            .map(|task| task.name.clone())
+            .collect();
+
+        Ok(affected)
+    }
+
+    pub fn open_preview(&mut self) {
+        if self.tasks.get_selected().is_some() {
+            // files may have changed since the preview was last opened
+            self.matches.clear();
+            self.spawn_shell_vars();
+            self.input_mode = InputMode::Preview;
+        }
+    }
+
+    pub fn open_environment(&mut self) {
+        if self.tasks.get_selected().is_some() {
+            self.spawn_shell_vars();
+            self.input_mode = InputMode::Environment;
+        }
+    }
+
+    pub fn open_dependencies(&mut self) {
+        if let Some(task) = self.tasks.get_selected() {
+            self.dependencies = Some(dependency_tree(&self.taskfile, &task));
+            self.input_mode = InputMode::Dependencies;
+        }
+    }
+
+    pub fn open_used_by(&mut self) {
+        if let Some(task) = self.tasks.get_selected() {
+            let callers = used_by(&self.taskfile, &task)
+                .into_iter()
+                .map(|(caller, call)| (caller.clone(), call))
+                .collect();
+            self.used_by = Some(callers);
+            self.input_mode = InputMode::UsedBy;
+        }
+    }
+
+    pub fn open_form(&mut self) {
+        if let Some(task) = self.tasks.get_selected() {
+            self.form = Some(VarForm::new(&self.taskfile, &task));
+            self.input_mode = InputMode::Variables;
+        }
+    }
+
+    /// Runs the task of the form, unless a required variable is empty.
+    pub fn submit_form(&mut self) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+
+        if let Some(idx) = form
+            .fields
+            .iter()
+            .position(|field| field.required && field.value.is_empty())
+        {
+            form.selected = idx;
+            form.error = Some(format!("{} is required", form.fields[idx].name));
+            return;
+        }
+
+        let invocation = form.invocation();
+        self.form = None;
+        self.run(invocation);
+    }
+
+    /// Runs a task, asking for the parts of a wildcard name and its
+    /// `CLI_ARGS` first if it needs them.
+    pub fn run(&mut self, invocation: Invocation) {
+        let Some(task) = self.find_task(&invocation.task).cloned() else {
+            self.execute(invocation);
+            return;
+        };
+
+        if task.is_wildcard() && invocation.task == task.name {
+            self.wildcard = Some(WildcardPrompt::new(invocation));
+            self.input_mode = InputMode::Wildcard;
+        } else if task.references("CLI_ARGS") {
+            let history = self.history.get(&self.taskfile.path, &task.name).to_vec();
+            self.args_prompt = Some(ArgsPrompt {
+                invocation,
+                value: String::new(),
+                history,
+                position: None,
+            });
+            self.input_mode = InputMode::CliArgs;
+        } else {
+            self.execute(invocation);
+        }
+    }
+
+    /// Runs the wildcard task under the name built from the entered parts.
+    pub fn submit_wildcard(&mut self) {
+        let Some(prompt) = &mut self.wildcard else {
+            return;
+        };
+
+        if let Some(idx) = prompt.segments.iter().position(String::is_empty) {
+            prompt.selected = idx;
+            prompt.error = Some("every wildcard needs a value".to_string());
+            return;
+        }
+
+        let mut invocation = prompt.invocation.clone();
+        invocation.task = prompt.name();
+        self.wildcard = None;
+        self.run(invocation);
+    }
+
+    pub fn submit_args(&mut self) {
+        let Some(prompt) = self.args_prompt.take() else {
+            return;
+        };
+        let mut invocation = prompt.invocation;
+        invocation.cli_args = split_args(&prompt.value);
+
+        // wildcard tasks share one history for all names
+        let name = match self.find_task(&invocation.task) {
+            Some(task) => task.name.clone(),
+            None => invocation.task.clone(),
+        };
+        if !prompt.value.trim().is_empty() {
+            self.history
+                .add(&self.taskfile.path, &name, prompt.value.trim());
+            // running the task matters more than remembering the arguments
+            _ = self.history.save();
+        }
+
+        self.execute(invocation);
+    }
+
+    /// Runs the task, unless it or a task it triggers has a `prompt` that was
+    /// not confirmed yet. `task` would ask for it with its output piped.
+    fn execute(&mut self, invocation: Invocation) {
+        let prompts = self.prompts(&invocation.task);
+        if !invocation.yes && !prompts.is_empty() {
+            self.confirmation = Some(Confirmation {
+                invocation,
+                prompts,
+            });
+            self.input_mode = InputMode::Confirm;
+            return;
+        }
+
+        self.task_to_exec = Some(invocation);
+        self.quit();
+    }
+
+    pub fn confirm(&mut self) {
+        if let Some(confirmation) = self.confirmation.take() {
+            let mut invocation = confirmation.invocation;
+            invocation.yes = true;
+            self.execute(invocation);
+        }
+    }
+
+    /// The prompts of the task and the tasks it triggers, by task name.
+    fn prompts(&self, name: &str) -> Vec<(String, String)> {
+        let Some(task) = self.find_task(name) else {
+            return Vec::new();
+        };
+
+        triggered(&self.taskfile, task)
+            .into_iter()
+            .filter(|task| !task.prompt.is_empty())
+            .flat_map(|dep| {
+                // the task itself may be a wildcard task called by a concrete name
+                let called = if dep.name == task.name {
+                    name
+                } else {
+                    &dep.name
+                };
+                resolve_call(&self.taskfile, dep, called)
+                    .prompt
+                    .into_iter()
+                    .map(move |prompt| (called.to_string(), prompt))
+            })
+            .collect()
+    }
+
+    /// The task run for a name given to `task`, which may be a wildcard task.
+    fn find_task(&self, name: &str) -> Option<&Task> {
+        lookup(&self.taskfile.tasks, "", name)
     }
 
     pub fn quit(&mut self) {
@@ -35,10 +404,237 @@ impl App {
     }
 }
 
+/// Computes the states of the tasks in a background thread, sending each one
+/// as soon as it is known.
+fn spawn_states(taskfile: &Taskfile, tasks: &[Task]) -> Receiver<(String, State)> {
+    let (tx, rx) = mpsc::channel();
+    let taskfile = taskfile.clone();
+    let tasks: Vec<Task> = tasks
+        .iter()
+        .filter(|task| !task.sources.is_empty())
+        .cloned()
+        .collect();
+
+    thread::spawn(move || {
+        for task in tasks {
+            if let Some(state) = fingerprint::state(&taskfile, &task) {
+                if tx.send((task.name, state)).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    rx
+}
+
 pub enum InputMode {
     Select,
     Search,
     Preview,
+    Environment,
+    Diagnostics,
+    Dependencies,
+    UsedBy,
+    Variables,
+    CliArgs,
+    Confirm,
+    Wildcard,
+}
+
+/// The values for the wildcards of a task name like `start:*`.
+pub struct WildcardPrompt {
+    pub invocation: Invocation,
+    /// The literal parts of the name between the wildcards.
+    pub parts: Vec<String>,
+    pub segments: Vec<String>,
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl WildcardPrompt {
+    fn new(invocation: Invocation) -> WildcardPrompt {
+        let parts: Vec<String> = invocation.task.split('*').map(String::from).collect();
+        WildcardPrompt {
+            segments: vec![String::new(); parts.len() - 1],
+            parts,
+            invocation,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// The task name with the wildcards replaced by the entered values.
+    pub fn name(&self) -> String {
+        let mut name = self.parts[0].clone();
+        for (segment, part) in self.segments.iter().zip(&self.parts[1..]) {
+            name.push_str(segment);
+            name.push_str(part);
+        }
+        name
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.segments.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.segments.len() - 1) % self.segments.len();
+    }
+
+    pub fn selected_segment(&mut self) -> &mut String {
+        self.error = None;
+        &mut self.segments[self.selected]
+    }
+}
+
+/// The prompts to confirm before running a task.
+pub struct Confirmation {
+    pub invocation: Invocation,
+    pub prompts: Vec<(String, String)>,
+}
+
+/// The arguments for a task using `CLI_ARGS`, with the ones given before.
+pub struct ArgsPrompt {
+    pub invocation: Invocation,
+    pub value: String,
+    /// Previous values for the task, newest first.
+    pub history: Vec<String>,
+    /// The history entry shown, `None` while typing a new value.
+    pub position: Option<usize>,
+}
+
+impl ArgsPrompt {
+    pub fn older(&mut self) {
+        let position = match self.position {
+            Some(idx) if idx + 1 < self.history.len() => idx + 1,
+            Some(idx) => idx,
+            None if !self.history.is_empty() => 0,
+            None => return,
+        };
+        self.position = Some(position);
+        self.value.clone_from(&self.history[position]);
+    }
+
+    pub fn newer(&mut self) {
+        match self.position {
+            Some(0) | None => {
+                self.position = None;
+                self.value.clear();
+            }
+            Some(idx) => {
+                self.position = Some(idx - 1);
+                self.value.clone_from(&self.history[idx - 1]);
+            }
+        }
+    }
+}
+
+/// The variables of a task, edited before running it.
+pub struct VarForm {
+    pub task: String,
+    pub fields: Vec<FormField>,
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+pub struct FormField {
+    pub name: String,
+    pub value: String,
+    /// The value defined in the Taskfile, `value` starts out with it unless it
+    /// comes from a command.
+    pub default: Option<String>,
+    /// Shown instead of an empty value, like the command of a `sh:` variable.
+    pub placeholder: Option<String>,
+    /// Listed in `requires.vars`.
+    pub required: bool,
+}
+
+impl VarForm {
+    fn new(taskfile: &Taskfile, task: &Task) -> VarForm {
+        let scope = task_scope(taskfile, task, None);
+        let field = |name: &str| {
+            let (default, placeholder) = match scope.get(name) {
+                Some(value) => (Some(value_to_string(value)), None),
+                // only values that do not need running anything are known,
+                // the definition of the others is shown instead
+                None => (
+                    None,
+                    task.vars
+                        .iter()
+                        .rev()
+                        .chain(task.include_vars.iter().rev())
+                        .chain(taskfile.vars.iter().rev())
+                        .find(|var| var.name == name)
+                        .map(|var| var.value.to_string()),
+                ),
+            };
+            // variables set by the Taskfile or its includes satisfy `requires`
+            let defined = default.is_some() || scope.dynamic.contains(name);
+            FormField {
+                name: name.to_string(),
+                value: default.clone().unwrap_or_default(),
+                default,
+                placeholder,
+                required: task.requires.iter().any(|required| required == name) && !defined,
+            }
+        };
+
+        let mut fields: Vec<FormField> = Vec::new();
+        for name in task
+            .vars
+            .iter()
+            .map(|var| &var.name)
+            .chain(task.requires.iter())
+        {
+            if !fields.iter().any(|field| &field.name == name) {
+                fields.push(field(name));
+            }
+        }
+
+        VarForm {
+            task: task.name.clone(),
+            fields,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.fields.is_empty() {
+            self.selected = (self.selected + 1) % self.fields.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.fields.is_empty() {
+            self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+        }
+    }
+
+    pub fn selected_field(&mut self) -> Option<&mut FormField> {
+        self.error = None;
+        self.fields.get_mut(self.selected)
+    }
+
+    /// Passes only the values that were changed or are required, so the
+    /// Taskfile keeps computing everything else.
+    fn invocation(&self) -> Invocation {
+        let mut invocation = Invocation::new(self.task.clone());
+        invocation.vars = self
+            .fields
+            .iter()
+            .filter(|field| {
+                let changed = match &field.default {
+                    Some(default) => &field.value != default,
+                    None => !field.value.is_empty(),
+                };
+                field.required || changed
+            })
+            .map(|field| (field.name.clone(), field.value.clone()))
+            .collect();
+        invocation
+    }
 }
 
 pub struct StatefulList {
@@ -121,9 +717,29 @@ impl StatefulList {
         }
     }
 
-    pub fn filter(&mut self, search: &String) {
+    /// Returns the task named or aliased exactly like the search, or else the
+    /// only task left after filtering.
+    pub fn unique_match(&self, search: &str) -> Option<Task> {
+        let exact = self
+            .items
+            .iter()
+            .find(|i| i.item.names().any(|name| name == search));
+
+        match (exact, self.items.as_slice()) {
+            (Some(i), _) | (None, [i]) => Some(i.item.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn filter(&mut self, search: &str, only: Option<&HashSet<String>>) {
         self.items.clone_from(&self.orig_items);
-        self.items.retain(|i| i.item.name.contains(search));
+        self.items.retain(|i| {
+            let listed = match only {
+                Some(names) => names.contains(&i.item.name),
+                None => true,
+            };
+            listed && i.item.names().any(|name| name.contains(search))
+        });
         self.state = ListState::default();
 
         if !self.items.is_empty() {
@@ -1,14 +1,24 @@
 use std::env;
+use std::time::Duration;
 
 use ratatui::style::Color;
 use std::str::FromStr;
 
 const ENV_PREFIX: &str = "TASKUI_";
 
+/// How tasks are listed that do not run on the current platform.
+#[derive(PartialEq)]
+pub enum OtherPlatforms {
+    Grey,
+    Hide,
+}
+
 pub struct Config {
     pub list_internal: bool,
+    pub other_platforms: OtherPlatforms,
     pub highlight_style_bg: Color,
     pub highlight_style_fg: Color,
+    pub sh_timeout: Duration,
 }
 
 impl Config {
@@ -18,6 +28,10 @@ impl Config {
                 .unwrap_or("false".to_string())
                 .parse()
                 .unwrap(),
+            other_platforms: match env::var(ENV_PREFIX.to_string() + "OTHER_PLATFORMS").as_deref() {
+                Ok("hide") => OtherPlatforms::Hide,
+                _ => OtherPlatforms::Grey,
+            },
             highlight_style_bg: env::var(ENV_PREFIX.to_string() + "HIGHLIGHT_STYLE_BG")
                 .unwrap_or("".to_string())
                 .parse()
@@ -26,6 +40,12 @@ impl Config {
                 .unwrap_or("".to_string())
                 .parse()
                 .unwrap_or(Color::from_str("#4c4f69").unwrap()),
+            sh_timeout: Duration::from_secs(
+                env::var(ENV_PREFIX.to_string() + "SH_TIMEOUT")
+                    .unwrap_or("".to_string())
+                    .parse()
+                    .unwrap_or(5),
+            ),
         }
     }
 }
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::trace::get_data_dir;
+
+const HISTORY_FILE: &str = "history.yml";
+const MAX_ENTRIES: usize = 20;
+
+/// Previously entered `CLI_ARGS`, newest first, by Taskfile and task.
+#[derive(Default)]
+pub struct History {
+    entries: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl History {
+    /// Loads the history, starting over if it cannot be read.
+    pub fn load() -> History {
+        let entries = std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|text| serde_yaml::from_str(&text).ok())
+            .unwrap_or_default();
+
+        History { entries }
+    }
+
+    pub fn get(&self, taskfile: &Path, task: &str) -> &[String] {
+        self.entries
+            .get(&taskfile.to_string_lossy().to_string())
+            .and_then(|tasks| tasks.get(task))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Moves the value to the front of the history of the task.
+    pub fn add(&mut self, taskfile: &Path, task: &str, value: &str) {
+        let values = self
+            .entries
+            .entry(taskfile.to_string_lossy().to_string())
+            .or_default()
+            .entry(task.to_string())
+            .or_default();
+
+        values.retain(|v| v != value);
+        values.insert(0, value.to_string());
+        values.truncate(MAX_ENTRIES);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(&self.entries)?)?;
+
+        Ok(())
+    }
+
+    fn path() -> PathBuf {
+        get_data_dir().join(HISTORY_FILE)
+    }
+}
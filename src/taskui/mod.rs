@@ -4,6 +4,8 @@ pub use self::app::App;
 mod config;
 pub use self::config::Config;
 
+mod history;
+
 pub mod event;
 
 mod update;
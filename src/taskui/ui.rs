@@ -2,15 +2,27 @@ use ratatui::{
     prelude::*,
     widgets::{ListItem, *},
 };
+use std::path::Path;
+use std::time::SystemTime;
 
-use crate::taskfile::config::Task;
+use crate::taskfile::dotenv::{is_secret, task_environment};
+use crate::taskfile::error::ParseError;
+use crate::taskfile::fingerprint::{matches, task_dir, State, Status};
+use crate::taskfile::graph::{Call, Node, NodeState};
+use crate::taskfile::task::{Run, Task, Var};
+use crate::taskfile::variables::resolve_task;
 
 use super::app::{App, InputMode};
 
+const DESC_GAP: usize = 2;
+
 pub fn render(f: &mut Frame, app: &mut App) {
     let mut search_chunk_size = 0;
 
-    if matches!(app.input_mode, InputMode::Search) || !app.search.is_empty() {
+    if matches!(app.input_mode, InputMode::Search)
+        || !app.search.is_empty()
+        || app.affected.is_some()
+    {
         search_chunk_size = 3;
     }
 
@@ -19,25 +31,47 @@ pub fn render(f: &mut Frame, app: &mut App) {
         .constraints([Constraint::Length(search_chunk_size), Constraint::Min(1)])
         .split(f.size());
 
+    let name_width = app
+        .tasks
+        .items
+        .iter()
+        .map(|i| formatted_name(&i.item).chars().count())
+        .max()
+        .unwrap_or(0);
+    // borders take up one column on each side
+    let list_width = chunks[1].width.saturating_sub(2) as usize;
+
+    let badges: Vec<Option<Span<'static>>> = app
+        .tasks
+        .items
+        .iter()
+        .map(|i| badge(app, &i.item))
+        .collect();
+    let badge_width = badges
+        .iter()
+        .flatten()
+        .map(|badge| badge.width() + DESC_GAP)
+        .max()
+        .unwrap_or(0);
+
     let items: Vec<ListItem> = app
         .tasks
         .items
         .iter()
-        .map(|i| formatted_list_item(&i.item))
+        .zip(badges)
+        .map(|(i, badge)| formatted_list_item(&i.item, badge, name_width, badge_width, list_width))
         .collect();
 
-    let items = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Tasks"))
-        .highlight_style(
-            Style::default()
-                .bg(app.cfg.highlight_style_bg)
-                .fg(app.cfg.highlight_style_fg)
-                .add_modifier(Modifier::BOLD),
-        );
+    let items = List::new(items).block(tasks_block(app)).highlight_style(
+        Style::default()
+            .bg(app.cfg.highlight_style_bg)
+            .fg(app.cfg.highlight_style_fg)
+            .add_modifier(Modifier::BOLD),
+    );
 
     let input = Paragraph::new(Text::from(app.search.clone()))
         .style(Style::default())
-        .block(Block::default().borders(Borders::ALL).title("Search"));
+        .block(search_block(app));
 
     f.render_widget(input, chunks[0]);
 
@@ -46,34 +80,888 @@ pub fn render(f: &mut Frame, app: &mut App) {
     match app.input_mode {
         InputMode::Search => f.set_cursor(1 + app.search.len() as u16, 1),
         InputMode::Preview => render_preview(f, app),
+        InputMode::Environment => render_environment(f, app),
+        InputMode::Diagnostics => render_diagnostics(f, app),
+        InputMode::Dependencies => render_dependencies(f, app),
+        InputMode::UsedBy => render_used_by(f, app),
+        InputMode::Variables => render_var_form(f, app),
+        InputMode::CliArgs => render_args_prompt(f, app),
+        InputMode::Confirm => render_confirmation(f, app),
+        InputMode::Wildcard => render_wildcard(f, app),
         _ => {}
     }
+
+    if let Some(err) = &app.load_error {
+        render_load_error(f, err);
+    }
 }
 
-fn formatted_list_item(task: &Task) -> ListItem<'_> {
+fn search_block(app: &App) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL).title("Search");
+
+    let (filter, color) = match &app.affected {
+        None => return block,
+        Some(Ok(_)) => (
+            " affected by git changes | a: show all ".to_string(),
+            Color::Green,
+        ),
+        Some(Err(err)) => (format!(" git: {} ", err), Color::Red),
+    };
+
+    block.title(
+        block::Title::from(Line::styled(filter, Style::default().fg(color)))
+            .alignment(Alignment::Right),
+    )
+}
+
+fn tasks_block(app: &App) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL).title("Tasks");
+
+    match app.taskfile.warnings.len() {
+        0 => block,
+        count => block.title(
+            block::Title::from(Line::styled(
+                format!(
+                    " {} warning{} | d: diagnostics ",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ),
+                Style::default().fg(Color::Yellow),
+            ))
+            .alignment(Alignment::Right),
+        ),
+    }
+}
+
+fn formatted_name(task: &Task) -> String {
+    let mut name = task.name.clone();
+
+    if !task.aliases.is_empty() {
+        name += &format!(" (aliases: {})", task.aliases.join(", "));
+    }
     if task.internal {
-        ListItem::new(task.name.as_str().to_owned() + " (internal)")
-            .style(Style::default().fg(Color::DarkGray))
+        name += " (internal)";
+    }
+    if task.is_wildcard() {
+        name += " (wildcard)";
+    }
+    if !task.matches_platform() {
+        name += &format!(" (platforms: {})", task.platforms.join(", "));
+    }
+
+    name
+}
+
+/// The up-to-date state shown next to a task with `sources`.
+fn badge(app: &App, task: &Task) -> Option<Span<'static>> {
+    match app.states.get(&task.name) {
+        Some(state) => Some(Span::styled(
+            formatted_state(state),
+            Style::default().fg(match state.status {
+                Status::UpToDate => Color::Green,
+                Status::Stale => Color::Yellow,
+            }),
+        )),
+        None if app.checking_state(task) => Some(Span::styled(
+            "checking...",
+            Style::default().fg(Color::DarkGray),
+        )),
+        None => None,
+    }
+}
+
+fn formatted_list_item(
+    task: &Task,
+    badge: Option<Span<'static>>,
+    name_width: usize,
+    badge_width: usize,
+    list_width: usize,
+) -> ListItem<'static> {
+    let name = formatted_name(task);
+    let desc_width = list_width.saturating_sub(name_width + DESC_GAP + badge_width);
+    let desc = truncate(
+        task.desc
+            .as_deref()
+            .unwrap_or("")
+            .lines()
+            .next()
+            .unwrap_or(""),
+        desc_width,
+    );
+
+    let style = if task.internal || !task.matches_platform() {
+        Style::default().fg(Color::DarkGray)
     } else {
-        ListItem::new(task.name.as_str()).style(Style::default())
+        Style::default()
+    };
+
+    if desc.is_empty() && badge.is_none() {
+        return ListItem::new(name).style(style);
+    }
+
+    let badge = match badge {
+        Some(badge) => Span::styled(
+            format!("{:<width$}", badge.content, width = badge_width),
+            badge.style,
+        ),
+        None => Span::raw(" ".repeat(badge_width)),
+    };
+
+    ListItem::new(Line::from(vec![
+        Span::raw(format!("{:<width$}", name, width = name_width + DESC_GAP)),
+        badge,
+        Span::styled(desc, Style::default().fg(Color::DarkGray)),
+    ]))
+    .style(style)
+}
+
+fn formatted_state(state: &State) -> String {
+    let status = match state.status {
+        Status::UpToDate => "up to date",
+        Status::Stale => "stale",
+    };
+
+    match state.last_run {
+        Some(last_run) => format!("{}, ran {}", status, time_ago(last_run)),
+        None => format!("{}, never ran", status),
+    }
+}
+
+fn time_ago(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    match width {
+        0 => String::new(),
+        _ => text.chars().take(width - 1).collect::<String>() + "…",
     }
 }
 
 pub fn render_preview(f: &mut Frame, app: &mut App) {
     let selected_task = app.tasks.get_selected().unwrap();
+    let (task, mode) = match (app.preview_resolved, app.evaluate_sh) {
+        _ if app.evaluating_sh() => (selected_task.clone(), "evaluating sh"),
+        (true, true) => (
+            resolve_task(&app.taskfile, &selected_task, Some(&mut app.shell_vars)),
+            "resolved, sh evaluated",
+        ),
+        (true, false) => (
+            resolve_task(&app.taskfile, &selected_task, None),
+            "resolved",
+        ),
+        (false, _) => (selected_task.clone(), "raw"),
+    };
+    let lines = if app.evaluating_sh() {
+        vec![evaluating_sh_placeholder()]
+    } else {
+        let mut preview = if app.preview_resolved {
+            formatted_preview(&task)
+        } else {
+            task.definition.trim_end().to_string()
+        };
+        if let Some(state) = app.states.get(&task.name) {
+            preview = format!("status: {}\n{}", formatted_state(state), preview);
+        }
+        let mut lines: Vec<Line> = preview
+            .lines()
+            .map(|line| Line::raw(line.to_string()))
+            .collect();
+        push_matched_files(&mut lines, app, &selected_task);
+        lines
+    };
+
+    let area = centered_rect(70, 90, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .style(Style::default())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Preview: {}",
+                    task.name.split(':').next_back().unwrap()
+                ))
+                .title(
+                    block::Title::from(format!(" {} | r: resolve, e: evaluate sh ", mode))
+                        .alignment(Alignment::Right),
+                ),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Lists the files matched by the `sources` and `generates` of a task, relative
+/// to the directory it runs in.
+fn push_matched_files(lines: &mut Vec<Line>, app: &mut App, task: &Task) {
+    if task.sources.is_empty() && task.generates.is_empty() {
+        return;
+    }
+
+    let shell = app.evaluate_sh.then_some(&mut app.shell_vars);
+    let task = resolve_task(&app.taskfile, task, shell);
+    let dir = task_dir(&app.taskfile, &task);
+
+    for (key, patterns) in [("sources", &task.sources), ("generates", &task.generates)] {
+        if patterns.is_empty() {
+            continue;
+        }
+
+        lines.push(Line::raw(format!("matched {}:", key)));
+        if patterns.iter().any(|pattern| pattern.contains("{{")) {
+            lines.push(Line::styled(
+                "  depends on dynamic variables, press e to evaluate them",
+                Style::default().fg(Color::DarkGray),
+            ));
+            continue;
+        }
+
+        let matches = app
+            .matches
+            .entry((dir.clone(), patterns.clone()))
+            .or_insert_with(|| matches(&dir, patterns));
+
+        for pattern in &matches.unmatched {
+            lines.push(Line::styled(
+                format!("  {} matches no files", pattern),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+
+        let paths: Vec<String> = matches
+            .files
+            .iter()
+            .map(|file| {
+                file.path
+                    .strip_prefix(&dir)
+                    .unwrap_or(&file.path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        let path_width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (path, file) in paths.iter().zip(matches.files.iter()) {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {:<width$}  ", path, width = path_width)),
+                Span::styled(
+                    format!(
+                        "{:>9}  {}",
+                        formatted_size(file.size),
+                        file.modified.map(time_ago).unwrap_or_default()
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+    }
+}
+
+fn evaluating_sh_placeholder() -> Line<'static> {
+    Line::styled(
+        "running the commands of the sh: variables...",
+        Style::default().fg(Color::DarkGray),
+    )
+}
+
+fn formatted_size(size: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+pub fn render_environment(f: &mut Frame, app: &mut App) {
+    let selected_task = app.tasks.get_selected().unwrap();
+    if app.evaluating_sh() {
+        render_environment_lines(
+            f,
+            &selected_task,
+            vec![evaluating_sh_placeholder()],
+            "evaluating sh",
+        );
+        return;
+    }
+    let shell = app.evaluate_sh.then_some(&mut app.shell_vars);
+    let env = task_environment(&app.taskfile, &selected_task, shell);
+
+    let mut lines: Vec<Line> = env
+        .missing
+        .iter()
+        .map(|path| {
+            Line::styled(
+                format!("dotenv file not found: {}", path.display()),
+                Style::default().fg(Color::Red),
+            )
+        })
+        .collect();
+
+    let key_width = env
+        .entries
+        .iter()
+        .map(|entry| entry.key.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines.extend(env.entries.iter().map(|entry| {
+        let value = if is_secret(&entry.key) {
+            "********".to_string()
+        } else {
+            entry.value.clone()
+        };
+
+        Line::from(vec![
+            Span::raw(format!(
+                "{:<width$} = {}  ",
+                entry.key,
+                value,
+                width = key_width
+            )),
+            Span::styled(
+                format!("({})", entry.source),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    }));
+
+    if lines.is_empty() {
+        lines.push(Line::raw("no environment variables defined"));
+    }
+
+    let mode = if app.evaluate_sh {
+        "sh evaluated"
+    } else {
+        "sh not evaluated"
+    };
+    render_environment_lines(f, &selected_task, lines, mode);
+}
+
+fn render_environment_lines(f: &mut Frame, task: &Task, lines: Vec<Line>, mode: &str) {
+    let area = centered_rect(70, 90, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .style(Style::default())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Environment: {}", task.name))
+                .title(
+                    block::Title::from(format!(" {} | e: evaluate sh ", mode))
+                        .alignment(Alignment::Right),
+                ),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_dependencies(f: &mut Frame, app: &mut App) {
+    let Some(tree) = &app.dependencies else {
+        return;
+    };
+
+    let mut lines = vec![Line::raw(tree.name.clone())];
+    push_tree(&mut lines, &tree.children, "");
+    if tree.children.is_empty() {
+        lines.push(Line::styled(
+            "no dependencies",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let area = centered_rect(70, 90, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .style(Style::default())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Dependencies: {}", tree.name)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn push_tree(lines: &mut Vec<Line>, nodes: &[Node], indent: &str) {
+    for (idx, node) in nodes.iter().enumerate() {
+        let last = idx == nodes.len() - 1;
+        let (branch, child_indent) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        let call = match node.call {
+            Some(Call::Dep) => "dep",
+            Some(Call::Cmd) => "cmd",
+            None => "",
+        };
+        let (style, suffix) = match node.state {
+            NodeState::Found => (Style::default(), format!(" ({})", call)),
+            NodeState::Missing => (
+                Style::default().fg(Color::Red),
+                format!(" ({}, not found)", call),
+            ),
+            NodeState::Cycle => (
+                Style::default().fg(Color::Yellow),
+                format!(" ({}, cycle)", call),
+            ),
+            NodeState::Repeated => (Style::default(), format!(" ({}, see above)", call)),
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}{}", indent, branch),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(node.name.clone(), style),
+            Span::styled(suffix, style.fg(style.fg.unwrap_or(Color::DarkGray))),
+        ]));
+
+        push_tree(
+            lines,
+            &node.children,
+            &format!("{}{}", indent, child_indent),
+        );
+    }
+}
+
+pub fn render_used_by(f: &mut Frame, app: &mut App) {
+    let Some(callers) = &app.used_by else {
+        return;
+    };
+    let selected_task = app.tasks.get_selected().unwrap();
+    let root_dir = app.taskfile.path.parent().unwrap_or(Path::new(""));
+
+    let mut lines: Vec<Line> = callers
+        .iter()
+        .map(|(caller, call)| {
+            let call = match call {
+                Call::Dep => "dep",
+                Call::Cmd => "cmd",
+            };
+            let file = caller
+                .taskfile
+                .strip_prefix(root_dir)
+                .unwrap_or(&caller.taskfile)
+                .display();
+
+            Line::from(vec![
+                Span::raw(formatted_name(caller)),
+                Span::styled(
+                    format!(" ({}, {})", call, file),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect();
+
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            "not used by any task",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
     let area = centered_rect(70, 90, f.size());
-    let paragraph = Paragraph::new(selected_task.body.as_str())
+    let paragraph = Paragraph::new(lines)
         .alignment(Alignment::Left)
         .style(Style::default())
-        .block(Block::default().borders(Borders::ALL).title(format!(
-            "Preview: {}",
-            selected_task.name.split(':').last().unwrap()
-        )));
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Used by: {}", selected_task.name)),
+        );
 
     f.render_widget(Clear, area);
     f.render_widget(paragraph, area);
 }
 
+pub fn render_diagnostics(f: &mut Frame, app: &mut App) {
+    let mut lines: Vec<Line> = Vec::new();
+
+    for warning in &app.taskfile.warnings {
+        lines.push(Line::from(vec![
+            Span::styled(warning.location(), Style::default().fg(Color::Yellow)),
+            Span::raw(format!(": {}", warning.message)),
+        ]));
+        if let Some(chain) = warning.included_from() {
+            lines.push(Line::styled(
+                format!("  included from: {}", chain),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        if let Some(hint) = &warning.hint {
+            lines.push(Line::styled(
+                format!("  hint: {}", hint),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
+    let area = centered_rect(70, 90, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Diagnostics"));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_var_form(f: &mut Frame, app: &mut App) {
+    let Some(form) = &app.form else {
+        return;
+    };
+
+    let name_width = form
+        .fields
+        .iter()
+        .map(|field| field.name.chars().count() + usize::from(field.required))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let name = if field.required {
+                format!("{}*", field.name)
+            } else {
+                field.name.clone()
+            };
+            let mut spans = vec![Span::raw(format!(
+                "{:<width$} = ",
+                name,
+                width = name_width
+            ))];
+
+            match &field.placeholder {
+                Some(placeholder) if field.value.is_empty() => spans.push(Span::styled(
+                    placeholder.clone(),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                _ => spans.push(Span::raw(field.value.clone())),
+            }
+            match &field.default {
+                Some(default) if *default != field.value => spans.push(Span::styled(
+                    format!("  (default: {})", default),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                _ => {}
+            }
+
+            let mut line = Line::from(spans);
+            if idx == form.selected {
+                line.patch_style(
+                    Style::default()
+                        .bg(app.cfg.highlight_style_bg)
+                        .fg(app.cfg.highlight_style_fg),
+                );
+            }
+            line
+        })
+        .collect();
+
+    if lines.is_empty() {
+        lines.push(Line::raw("no variables defined, Enter runs the task"));
+    }
+    if let Some(error) = &form.error {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+    }
+
+    let area = centered_rect(70, 50, f.size());
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Left).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Variables: {}", form.task))
+            .title(block::Title::from(" Enter: run | Esc: cancel ").alignment(Alignment::Right)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+
+    // place the cursor behind the value being edited
+    if let Some(field) = form.fields.get(form.selected) {
+        let x = area.x + 1 + (name_width + 3 + field.value.chars().count()) as u16;
+        let y = area.y + 1 + form.selected as u16;
+        if x < area.right() - 1 && y < area.bottom() - 1 {
+            f.set_cursor(x, y);
+        }
+    }
+}
+
+pub fn render_args_prompt(f: &mut Frame, app: &mut App) {
+    let Some(prompt) = &app.args_prompt else {
+        return;
+    };
+
+    let prefix = format!("task {} -- ", prompt.invocation.task);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(prefix.clone(), Style::default().fg(Color::DarkGray)),
+            Span::raw(prompt.value.clone()),
+        ]),
+        Line::raw(""),
+    ];
+
+    if prompt.history.is_empty() {
+        lines.push(Line::styled(
+            "no previous arguments",
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else {
+        lines.push(Line::styled(
+            "previous arguments:",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.extend(prompt.history.iter().enumerate().map(|(idx, value)| {
+            if prompt.position == Some(idx) {
+                Line::styled(
+                    format!("  {}", value),
+                    Style::default()
+                        .bg(app.cfg.highlight_style_bg)
+                        .fg(app.cfg.highlight_style_fg),
+                )
+            } else {
+                Line::raw(format!("  {}", value))
+            }
+        }));
+    }
+
+    let area = centered_rect(70, 50, f.size());
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Left).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("CLI_ARGS: {}", prompt.invocation.task))
+            .title(
+                block::Title::from(" Enter: run | Up/Down: history | Esc: cancel ")
+                    .alignment(Alignment::Right),
+            ),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+
+    let x = area.x + 1 + (prefix.chars().count() + prompt.value.chars().count()) as u16;
+    if x < area.right() - 1 {
+        f.set_cursor(x, area.y + 1);
+    }
+}
+
+pub fn render_wildcard(f: &mut Frame, app: &mut App) {
+    let Some(prompt) = &app.wildcard else {
+        return;
+    };
+
+    let label_width = format!("MATCH[{}]", prompt.segments.len() - 1).len();
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("task ", Style::default().fg(Color::DarkGray)),
+            Span::raw(prompt.name()),
+        ]),
+        Line::raw(""),
+    ];
+    lines.extend(prompt.segments.iter().enumerate().map(|(idx, segment)| {
+        let mut line = Line::raw(format!(
+            "{:<width$} = {}",
+            format!("MATCH[{}]", idx),
+            segment,
+            width = label_width
+        ));
+        if idx == prompt.selected {
+            line.patch_style(
+                Style::default()
+                    .bg(app.cfg.highlight_style_bg)
+                    .fg(app.cfg.highlight_style_fg),
+            );
+        }
+        line
+    }));
+    if let Some(error) = &prompt.error {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+    }
+
+    let area = centered_rect(70, 50, f.size());
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Left).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Wildcard: {}", prompt.invocation.task))
+            .title(block::Title::from(" Enter: run | Esc: cancel ").alignment(Alignment::Right)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+
+    let segment = &prompt.segments[prompt.selected];
+    let x = area.x + 1 + (label_width + 3 + segment.chars().count()) as u16;
+    let y = area.y + 3 + prompt.selected as u16;
+    if x < area.right() - 1 && y < area.bottom() - 1 {
+        f.set_cursor(x, y);
+    }
+}
+
+pub fn render_confirmation(f: &mut Frame, app: &mut App) {
+    let Some(confirmation) = &app.confirmation else {
+        return;
+    };
+
+    let mut lines = Vec::new();
+    for (name, prompt) in &confirmation.prompts {
+        if name != &confirmation.invocation.task {
+            lines.push(Line::styled(
+                format!("{}:", name),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::styled(
+            prompt.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let area = centered_rect(60, 30, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(format!("Run {}?", confirmation.invocation.task))
+                .title(block::Title::from(" y: yes | n: no ").alignment(Alignment::Right)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_load_error(f: &mut Frame, err: &ParseError) {
+    let mut lines = vec![
+        Line::styled(err.message.clone(), Style::default().fg(Color::Red)),
+        Line::raw(""),
+        Line::raw(format!("at: {}", err.location())),
+    ];
+    if let Some(chain) = err.included_from() {
+        lines.push(Line::raw(format!("included from: {}", chain)));
+    }
+    if let Some(hint) = &err.hint {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!("hint: {}", hint),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let area = centered_rect(70, 50, f.size());
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Failed to load Taskfile")
+                .title(block::Title::from(" q: quit ").alignment(Alignment::Right)),
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn formatted_preview(task: &Task) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    let mut push_value = |key: &str, value: &Option<String>| {
+        if let Some(value) = value {
+            lines.push(format!("{}: {}", key, value));
+        }
+    };
+    push_value("desc", &task.desc);
+    push_value("summary", &task.summary);
+    push_value("dir", &task.dir);
+    push_value("label", &task.label);
+
+    if task.run != Run::Always {
+        lines.push(format!("run: {}", task.run));
+    }
+    if task.silent {
+        lines.push("silent: true".to_string());
+    }
+    if task.interactive {
+        lines.push("interactive: true".to_string());
+    }
+
+    push_list(&mut lines, "prompt", task.prompt.iter());
+    push_list(&mut lines, "aliases", task.aliases.iter());
+    push_list(&mut lines, "platforms", task.platforms.iter());
+    push_vars(&mut lines, "include vars", &task.include_vars);
+    push_vars(&mut lines, "vars", &task.vars);
+    push_vars(&mut lines, "env", &task.env);
+    push_list(&mut lines, "dotenv", task.dotenv.iter());
+    push_list(&mut lines, "deps", task.deps.iter());
+    push_list(&mut lines, "preconditions", task.preconditions.iter());
+    push_list(&mut lines, "requires", task.requires.iter());
+    push_list(&mut lines, "sources", task.sources.iter());
+    push_list(&mut lines, "generates", task.generates.iter());
+    push_list(&mut lines, "cmds", task.cmds.iter());
+
+    lines.join("\n")
+}
+
+fn push_list<T: ToString>(lines: &mut Vec<String>, key: &str, items: impl Iterator<Item = T>) {
+    let items: Vec<String> = items
+        .map(|item| format!("  - {}", item.to_string()))
+        .collect();
+
+    if !items.is_empty() {
+        lines.push(format!("{}:", key));
+        lines.extend(items);
+    }
+}
+
+fn push_vars(lines: &mut Vec<String>, key: &str, vars: &[Var]) {
+    if !vars.is_empty() {
+        lines.push(format!("{}:", key));
+        lines.extend(vars.iter().map(|var| {
+            if key == "env" && is_secret(&var.name) {
+                format!("  {}: ********", var.name)
+            } else {
+                format!("  {}: {}", var.name, var.value)
+            }
+        }));
+    }
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1,39 +1,51 @@
 use crossterm::event::{KeyCode, KeyEvent};
 
+use crate::taskfile::command::Invocation;
+
 use super::app::{App, InputMode};
 
 pub fn update(app: &mut App, key_event: KeyEvent) {
+    if app.load_error.is_some() {
+        if let KeyCode::Char('q') | KeyCode::Esc = key_event.code {
+            app.quit();
+        }
+        return;
+    }
+
     match app.input_mode {
         InputMode::Select => match key_event.code {
             KeyCode::Char('q') => app.quit(),
-            KeyCode::Char('p') => {
-                if app.tasks.get_selected().is_some() {
-                    app.input_mode = InputMode::Preview;
-                }
+            KeyCode::Char('p') => app.open_preview(),
+            KeyCode::Char('v') => app.open_environment(),
+            KeyCode::Char('t') => app.open_dependencies(),
+            KeyCode::Char('u') => app.open_used_by(),
+            KeyCode::Char('d') if !app.taskfile.warnings.is_empty() => {
+                app.input_mode = InputMode::Diagnostics;
             }
+            KeyCode::Char('f') if app.tasks.get_selected().is_some() => app.open_form(),
             KeyCode::Enter => {
-                if app.tasks.get_selected().is_some() {
-                    app.task_to_exec = app.tasks.get_selected();
-                    app.quit()
+                if let Some(task) = app.tasks.get_selected() {
+                    app.run(Invocation::new(task.name));
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => app.tasks.next(),
             KeyCode::Up | KeyCode::Char('k') => app.tasks.previous(),
             KeyCode::Char('/') => app.input_mode = InputMode::Search,
+            KeyCode::Char('a') => app.toggle_affected(),
             _ => {}
         },
         InputMode::Search => match key_event.code {
             KeyCode::Char(c) => {
                 app.search.push(c);
-                app.tasks.filter(&app.search);
+                app.filter();
             }
             KeyCode::Backspace => {
                 _ = app.search.pop();
-                app.tasks.filter(&app.search);
+                app.filter();
             }
             KeyCode::Esc => {
                 app.search = String::new();
-                app.tasks.filter(&app.search);
+                app.filter();
                 app.input_mode = InputMode::Select;
             }
             KeyCode::Enter => app.input_mode = InputMode::Select,
@@ -41,6 +53,110 @@ pub fn update(app: &mut App, key_event: KeyEvent) {
         },
         InputMode::Preview => match key_event.code {
             KeyCode::Char('q') | KeyCode::Char('p') => app.input_mode = InputMode::Select,
+            KeyCode::Char('r') => app.preview_resolved = !app.preview_resolved,
+            KeyCode::Char('e') => {
+                app.toggle_evaluate_sh();
+                app.preview_resolved |= app.evaluate_sh;
+            }
+            _ => {}
+        },
+        InputMode::Environment => match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('v') => app.input_mode = InputMode::Select,
+            KeyCode::Char('e') => app.toggle_evaluate_sh(),
+            _ => {}
+        },
+        InputMode::Dependencies => match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('t') => {
+                app.dependencies = None;
+                app.input_mode = InputMode::Select;
+            }
+            _ => {}
+        },
+        InputMode::UsedBy => match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('u') => {
+                app.used_by = None;
+                app.input_mode = InputMode::Select;
+            }
+            _ => {}
+        },
+        InputMode::Diagnostics => match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('d') => app.input_mode = InputMode::Select,
+            _ => {}
+        },
+        InputMode::Variables => {
+            let Some(form) = &mut app.form else {
+                app.input_mode = InputMode::Select;
+                return;
+            };
+            match key_event.code {
+                KeyCode::Esc => {
+                    app.form = None;
+                    app.input_mode = InputMode::Select;
+                }
+                KeyCode::Down | KeyCode::Tab => form.next(),
+                KeyCode::Up | KeyCode::BackTab => form.previous(),
+                KeyCode::Char(c) => {
+                    if let Some(field) = form.selected_field() {
+                        field.value.push(c);
+                    }
+                }
+                KeyCode::Backspace => {
+                    if let Some(field) = form.selected_field() {
+                        _ = field.value.pop();
+                    }
+                }
+                KeyCode::Enter => app.submit_form(),
+                _ => {}
+            }
+        }
+        InputMode::CliArgs => {
+            let Some(prompt) = &mut app.args_prompt else {
+                app.input_mode = InputMode::Select;
+                return;
+            };
+            match key_event.code {
+                KeyCode::Esc => {
+                    app.args_prompt = None;
+                    app.input_mode = InputMode::Select;
+                }
+                KeyCode::Up => prompt.older(),
+                KeyCode::Down => prompt.newer(),
+                KeyCode::Char(c) => {
+                    prompt.position = None;
+                    prompt.value.push(c);
+                }
+                KeyCode::Backspace => {
+                    prompt.position = None;
+                    _ = prompt.value.pop();
+                }
+                KeyCode::Enter => app.submit_args(),
+                _ => {}
+            }
+        }
+        InputMode::Wildcard => {
+            let Some(prompt) = &mut app.wildcard else {
+                app.input_mode = InputMode::Select;
+                return;
+            };
+            match key_event.code {
+                KeyCode::Esc => {
+                    app.wildcard = None;
+                    app.input_mode = InputMode::Select;
+                }
+                KeyCode::Down | KeyCode::Tab => prompt.next(),
+                KeyCode::Up | KeyCode::BackTab => prompt.previous(),
+                KeyCode::Char(c) => prompt.selected_segment().push(c),
+                KeyCode::Backspace => _ = prompt.selected_segment().pop(),
+                KeyCode::Enter => app.submit_wildcard(),
+                _ => {}
+            }
+        }
+        InputMode::Confirm => match key_event.code {
+            KeyCode::Char('y') => app.confirm(),
+            KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                app.confirmation = None;
+                app.input_mode = InputMode::Select;
+            }
             _ => {}
         },
     }